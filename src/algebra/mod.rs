@@ -0,0 +1,244 @@
+use std::collections::BTreeSet;
+
+// A Boolean algebra over `Element`. The algebra itself is a value so that
+// carriers such as subsets of a universe can hold what `top` and `complement`
+// depend on. `xor`, `implies` and `equiv` follow from the other operations.
+// Marker types carry the impls because one element type can have several algebras.
+pub trait BooleanAlgebra {
+    type Element: Clone;
+
+    fn top(&self) -> Self::Element;
+    fn bottom(&self) -> Self::Element;
+    fn meet(&self, left: &Self::Element, right: &Self::Element) -> Self::Element;
+    fn join(&self, left: &Self::Element, right: &Self::Element) -> Self::Element;
+    fn complement(&self, value: &Self::Element) -> Self::Element;
+
+    fn xor(&self, left: &Self::Element, right: &Self::Element) -> Self::Element {
+        let left_only = self.meet(left, &self.complement(right));
+        let right_only = self.meet(&self.complement(left), right);
+        self.join(&left_only, &right_only)
+    }
+
+    fn implies(&self, left: &Self::Element, right: &Self::Element) -> Self::Element {
+        self.join(&self.complement(left), right)
+    }
+
+    fn equiv(&self, left: &Self::Element, right: &Self::Element) -> Self::Element {
+        self.complement(&self.xor(left, right))
+    }
+}
+
+// The two-element algebra of `bool`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Bools;
+
+impl BooleanAlgebra for Bools {
+    type Element = bool;
+
+    fn top(&self) -> bool {
+        true
+    }
+
+    fn bottom(&self) -> bool {
+        false
+    }
+
+    fn meet(&self, left: &bool, right: &bool) -> bool {
+        *left && *right
+    }
+
+    fn join(&self, left: &bool, right: &bool) -> bool {
+        *left || *right
+    }
+
+    fn complement(&self, value: &bool) -> bool {
+        !value
+    }
+
+    fn xor(&self, left: &bool, right: &bool) -> bool {
+        left != right
+    }
+}
+
+// 64 independent `bool`s per `u64`, one per bit, as used by the bitsliced
+// truth table evaluator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lanes;
+
+impl BooleanAlgebra for Lanes {
+    type Element = u64;
+
+    fn top(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn bottom(&self) -> u64 {
+        0
+    }
+
+    fn meet(&self, left: &u64, right: &u64) -> u64 {
+        left & right
+    }
+
+    fn join(&self, left: &u64, right: &u64) -> u64 {
+        left | right
+    }
+
+    fn complement(&self, value: &u64) -> u64 {
+        !value
+    }
+
+    fn xor(&self, left: &u64, right: &u64) -> u64 {
+        left ^ right
+    }
+}
+
+// Subsets of a finite universe, complements being taken within it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sets<T: Ord> {
+    pub universe: BTreeSet<T>,
+}
+
+impl<T: Ord + Clone> Sets<T> {
+    pub fn new(universe: BTreeSet<T>) -> Self {
+        Sets { universe }
+    }
+}
+
+impl<T: Ord + Clone> BooleanAlgebra for Sets<T> {
+    type Element = BTreeSet<T>;
+
+    fn top(&self) -> BTreeSet<T> {
+        self.universe.clone()
+    }
+
+    fn bottom(&self) -> BTreeSet<T> {
+        BTreeSet::new()
+    }
+
+    fn meet(&self, left: &BTreeSet<T>, right: &BTreeSet<T>) -> BTreeSet<T> {
+        left.intersection(right).cloned().collect()
+    }
+
+    fn join(&self, left: &BTreeSet<T>, right: &BTreeSet<T>) -> BTreeSet<T> {
+        left.union(right).cloned().collect()
+    }
+
+    fn complement(&self, value: &BTreeSet<T>) -> BTreeSet<T> {
+        self.universe.difference(value).cloned().collect()
+    }
+
+    fn xor(&self, left: &BTreeSet<T>, right: &BTreeSet<T>) -> BTreeSet<T> {
+        left.symmetric_difference(right).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+    use crate::truth_table::{Bitsliced, Rows};
+    use std::collections::HashMap;
+
+    // The divisors of a square-free number, ordered by divisibility.
+    struct Divisors(u64);
+
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    impl BooleanAlgebra for Divisors {
+        type Element = u64;
+
+        fn top(&self) -> u64 {
+            self.0
+        }
+
+        fn bottom(&self) -> u64 {
+            1
+        }
+
+        fn meet(&self, left: &u64, right: &u64) -> u64 {
+            gcd(*left, *right)
+        }
+
+        fn join(&self, left: &u64, right: &u64) -> u64 {
+            left * right / gcd(*left, *right)
+        }
+
+        fn complement(&self, value: &u64) -> u64 {
+            self.0 / value
+        }
+    }
+
+    fn bind<E: Clone>(names: &[&str], values: &[E]) -> HashMap<String, E> {
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .zip(values.iter().cloned())
+            .collect()
+    }
+
+    #[test]
+    fn test_bools() {
+        let formula = rpn_to_ast("AB>C^").unwrap();
+        for (mask, output) in Rows::new(&formula).unwrap() {
+            let values: Vec<bool> = (0..3).map(|i| mask >> (2 - i) & 1 == 1).collect();
+            let vars = bind(&["A", "B", "C"], &values);
+            assert_eq!(formula.evaluate_in(&Bools, &vars), output);
+        }
+    }
+
+    #[test]
+    fn test_lanes_match_bitsliced() {
+        let formula = rpn_to_ast("AB=C!&D|AD>^").unwrap();
+        let lanes = [
+            0x0123_4567_89ab_cdef,
+            0xfedc_ba98_7654_3210,
+            0xdead_beef_0bad_f00d,
+            0x5555_0000_ffff_aaaa,
+        ];
+        let vars = bind(&["A", "B", "C", "D"], &lanes);
+        assert_eq!(
+            formula.evaluate_in(&Lanes, &vars),
+            Bitsliced::new(&formula).unwrap().eval(&lanes)
+        );
+    }
+
+    #[test]
+    fn test_sets() {
+        let algebra = Sets::new((1..=6).collect());
+        let vars = bind(
+            &["A", "B"],
+            &[BTreeSet::from([1, 2, 3]), BTreeSet::from([3, 4])],
+        );
+        let eval = |expression: &str| rpn_to_ast(expression).unwrap().evaluate_in(&algebra, &vars);
+        assert_eq!(eval("AB&"), BTreeSet::from([3]));
+        assert_eq!(eval("AB^"), BTreeSet::from([1, 2, 4]));
+        assert_eq!(eval("A!"), BTreeSet::from([4, 5, 6]));
+        assert_eq!(eval("AB>"), BTreeSet::from([3, 4, 5, 6]));
+        assert_eq!(eval("AB="), BTreeSet::from([3, 5, 6]));
+        assert_eq!(eval("1"), algebra.universe);
+        assert_eq!(eval("C"), BTreeSet::new());
+    }
+
+    #[test]
+    fn test_user_algebra() {
+        let algebra = Divisors(30);
+        let vars = bind(&["A", "B"], &[6, 10]);
+        let eval = |expression: &str| rpn_to_ast(expression).unwrap().evaluate_in(&algebra, &vars);
+        assert_eq!(eval("AB&"), 2);
+        assert_eq!(eval("AB|"), 30);
+        assert_eq!(eval("A!"), 5);
+        assert_eq!(eval("AB^"), 15);
+        assert_eq!(eval("AB>"), 10);
+        assert_eq!(eval("0"), 1);
+        // De Morgan and absorption hold in every Boolean algebra.
+        assert_eq!(eval("AB&!"), eval("A!B!|"));
+        assert_eq!(eval("AAB|&"), eval("A"));
+    }
+}
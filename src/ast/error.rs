@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    EmptyInput,
+    UnknownSymbol {
+        position: usize,
+        symbol: char,
+    },
+    StackUnderflow {
+        position: usize,
+        operator: char,
+    },
+    // `position` and `symbol` point at the first operand that no operator consumed.
+    LeftoverOperands {
+        position: usize,
+        symbol: char,
+        count: usize,
+    },
+    UnexpectedSymbol {
+        position: usize,
+        symbol: char,
+    },
+    UnexpectedEnd {
+        position: usize,
+    },
+    UnclosedParenthesis {
+        position: usize,
+    },
+    UnclosedBracket {
+        position: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyInput => write!(f, "empty expression"),
+            ParseError::UnknownSymbol { position, symbol } => {
+                write!(f, "unknown symbol '{}' at offset {}", symbol, position)
+            }
+            ParseError::StackUnderflow { position, operator } => write!(
+                f,
+                "missing operand for operator '{}' at offset {}",
+                operator, position
+            ),
+            ParseError::LeftoverOperands {
+                position,
+                symbol,
+                count,
+            } => write!(
+                f,
+                "{} operands left on the stack, first unused operand '{}' at offset {}",
+                count, symbol, position
+            ),
+            ParseError::UnexpectedSymbol { position, symbol } => {
+                write!(f, "unexpected symbol '{}' at offset {}", symbol, position)
+            }
+            ParseError::UnexpectedEnd { position } => {
+                write!(f, "unexpected end of expression at offset {}", position)
+            }
+            ParseError::UnclosedParenthesis { position } => {
+                write!(
+                    f,
+                    "parenthesis opened at offset {} is never closed",
+                    position
+                )
+            }
+            ParseError::UnclosedBracket { position } => {
+                write!(f, "bracket opened at offset {} is never closed", position)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,680 @@
+use super::variables::{is_identifier_char, is_identifier_start, is_valid_name};
+use super::{read_bracketed_name, Formula, ParseError};
+
+// The first symbol of every list is the canonical one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbols {
+    pub not: Vec<String>,
+    pub and: Vec<String>,
+    pub or: Vec<String>,
+    pub xor: Vec<String>,
+    pub implies: Vec<String>,
+    pub equiv: Vec<String>,
+}
+
+fn to_strings(symbols: &[&str]) -> Vec<String> {
+    symbols.iter().map(|s| s.to_string()).collect()
+}
+
+impl Symbols {
+    pub fn ascii() -> Self {
+        Symbols {
+            not: to_strings(&["!", "~"]),
+            and: to_strings(&["&", "&&"]),
+            or: to_strings(&["|", "||"]),
+            xor: to_strings(&["^"]),
+            implies: to_strings(&["->", "=>", ">"]),
+            equiv: to_strings(&["<->", "<=>", "="]),
+        }
+    }
+
+    pub fn unicode() -> Self {
+        Symbols {
+            not: to_strings(&["¬"]),
+            and: to_strings(&["∧"]),
+            or: to_strings(&["∨"]),
+            xor: to_strings(&["⊕"]),
+            implies: to_strings(&["→"]),
+            equiv: to_strings(&["↔"]),
+        }
+    }
+
+    pub fn latex() -> Self {
+        Symbols {
+            not: to_strings(&["\\neg", "\\lnot"]),
+            and: to_strings(&["\\land", "\\wedge"]),
+            or: to_strings(&["\\lor", "\\vee"]),
+            xor: to_strings(&["\\oplus"]),
+            implies: to_strings(&["\\rightarrow", "\\to"]),
+            equiv: to_strings(&["\\leftrightarrow", "\\iff"]),
+        }
+    }
+
+    pub fn merge(mut self, other: Symbols) -> Self {
+        self.not.extend(other.not);
+        self.and.extend(other.and);
+        self.or.extend(other.or);
+        self.xor.extend(other.xor);
+        self.implies.extend(other.implies);
+        self.equiv.extend(other.equiv);
+        self
+    }
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Symbols::ascii().merge(Symbols::unicode())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    And,
+    Or,
+    Xor,
+    Implies,
+    Equiv,
+}
+
+impl BinaryOp {
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Equiv => 1,
+            BinaryOp::Implies => 2,
+            BinaryOp::Or => 3,
+            BinaryOp::Xor => 4,
+            BinaryOp::And => 5,
+        }
+    }
+
+    fn of(node: &Formula) -> Option<(BinaryOp, &Formula, &Formula)> {
+        match node {
+            Formula::And(left, right) => Some((BinaryOp::And, left, right)),
+            Formula::Or(left, right) => Some((BinaryOp::Or, left, right)),
+            Formula::Xor(left, right) => Some((BinaryOp::Xor, left, right)),
+            Formula::Implies(left, right) => Some((BinaryOp::Implies, left, right)),
+            Formula::Equiv(left, right) => Some((BinaryOp::Equiv, left, right)),
+            _ => None,
+        }
+    }
+
+    fn symbol(self, symbols: &Symbols) -> &str {
+        let list = match self {
+            BinaryOp::And => &symbols.and,
+            BinaryOp::Or => &symbols.or,
+            BinaryOp::Xor => &symbols.xor,
+            BinaryOp::Implies => &symbols.implies,
+            BinaryOp::Equiv => &symbols.equiv,
+        };
+        &list[0]
+    }
+
+    fn right_associative(self) -> bool {
+        self == BinaryOp::Implies
+    }
+
+    fn build(self, left: Formula, right: Formula) -> Formula {
+        let (left, right) = (Box::new(left), Box::new(right));
+        match self {
+            BinaryOp::And => Formula::And(left, right),
+            BinaryOp::Or => Formula::Or(left, right),
+            BinaryOp::Xor => Formula::Xor(left, right),
+            BinaryOp::Implies => Formula::Implies(left, right),
+            BinaryOp::Equiv => Formula::Equiv(left, right),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Var(String),
+    Const(bool),
+    Not,
+    Binary(BinaryOp),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    token: Token,
+    position: usize,
+    symbol: char,
+}
+
+fn match_symbol(chars: &[char], candidates: &[String]) -> usize {
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let mut len = 0;
+            for c in candidate.chars() {
+                if chars.get(len) != Some(&c) {
+                    return None;
+                }
+                len += 1;
+            }
+            Some(len)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// Returns the operator token with the longest symbol at the start of `chars`.
+fn match_operator(chars: &[char], symbols: &Symbols) -> (Token, usize) {
+    let candidates = [
+        (Token::Not, &symbols.not),
+        (Token::Binary(BinaryOp::And), &symbols.and),
+        (Token::Binary(BinaryOp::Or), &symbols.or),
+        (Token::Binary(BinaryOp::Xor), &symbols.xor),
+        (Token::Binary(BinaryOp::Implies), &symbols.implies),
+        (Token::Binary(BinaryOp::Equiv), &symbols.equiv),
+    ];
+    candidates
+        .into_iter()
+        .map(|(token, list)| (token, match_symbol(chars, list)))
+        .max_by_key(|(_, len)| *len)
+        .unwrap()
+}
+
+fn tokenize(expression: &str, symbols: &Symbols) -> Result<Vec<Spanned>, ParseError> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < chars.len() {
+        let symbol = chars[position];
+        if symbol.is_whitespace() {
+            position += 1;
+            continue;
+        }
+
+        let simple = match symbol {
+            '0' => Some(Token::Const(false)),
+            '1' => Some(Token::Const(true)),
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            _ => None,
+        };
+        if let Some(token) = simple {
+            tokens.push(Spanned {
+                token,
+                position,
+                symbol,
+            });
+            position += 1;
+            continue;
+        }
+
+        if symbol == '[' {
+            let mut rest = chars.iter().copied().enumerate().skip(position + 1);
+            let name = read_bracketed_name(position, &mut rest)?;
+            // Names are ASCII, so the brackets and the name span len + 2 chars.
+            let len = name.len() + 2;
+            tokens.push(Spanned {
+                token: Token::Var(name),
+                position,
+                symbol,
+            });
+            position += len;
+            continue;
+        }
+
+        let rest = &chars[position..];
+        let identifier_len = if is_identifier_start(symbol) {
+            rest.iter().take_while(|&&c| is_identifier_char(c)).count()
+        } else {
+            0
+        };
+        let (token, len) = match_operator(rest, symbols);
+        if identifier_len > len {
+            tokens.push(Spanned {
+                token: Token::Var(rest[..identifier_len].iter().collect()),
+                position,
+                symbol,
+            });
+            position += identifier_len;
+            continue;
+        }
+        if len == 0 {
+            return Err(ParseError::UnknownSymbol { position, symbol });
+        }
+        tokens.push(Spanned {
+            token,
+            position,
+            symbol,
+        });
+        position += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    next: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Spanned> {
+        self.tokens.get(self.next)
+    }
+
+    fn advance(&mut self) -> Option<Spanned> {
+        let token = self.peek().cloned();
+        self.next += 1;
+        token
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Formula, ParseError> {
+        let mut left = self.parse_unary()?;
+        while let Some(&Spanned {
+            token: Token::Binary(op),
+            ..
+        }) = self.peek()
+        {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.advance();
+            let next_precedence = if op.right_associative() {
+                op.precedence()
+            } else {
+                op.precedence() + 1
+            };
+            let right = self.parse_binary(next_precedence)?;
+            left = op.build(left, right);
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Formula, ParseError> {
+        let Some(spanned) = self.advance() else {
+            return Err(ParseError::UnexpectedEnd { position: self.end });
+        };
+        match spanned.token {
+            Token::Var(name) => Ok(Formula::Var(name)),
+            Token::Const(val) => Ok(Formula::Const(val)),
+            Token::Not => Ok(Formula::Not(Box::new(self.parse_unary()?))),
+            Token::Open => {
+                let inner = self.parse_binary(0)?;
+                match self.advance() {
+                    Some(Spanned {
+                        token: Token::Close,
+                        ..
+                    }) => Ok(inner),
+                    Some(other) => Err(ParseError::UnexpectedSymbol {
+                        position: other.position,
+                        symbol: other.symbol,
+                    }),
+                    None => Err(ParseError::UnclosedParenthesis {
+                        position: spanned.position,
+                    }),
+                }
+            }
+            Token::Binary(_) | Token::Close => Err(ParseError::UnexpectedSymbol {
+                position: spanned.position,
+                symbol: spanned.symbol,
+            }),
+        }
+    }
+}
+
+pub fn infix_to_ast(expression: &str) -> Result<Formula, ParseError> {
+    infix_to_ast_with(expression, &Symbols::default())
+}
+
+pub fn infix_to_ast_with(expression: &str, symbols: &Symbols) -> Result<Formula, ParseError> {
+    let tokens = tokenize(expression, symbols)?;
+    if tokens.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+    let mut parser = Parser {
+        tokens,
+        next: 0,
+        end: expression.chars().count(),
+    };
+    let ast = parser.parse_binary(0)?;
+    match parser.peek() {
+        None => Ok(ast),
+        Some(extra) => Err(ParseError::UnexpectedSymbol {
+            position: extra.position,
+            symbol: extra.symbol,
+        }),
+    }
+}
+
+const NOT_PRECEDENCE: u8 = 6;
+const ATOM_PRECEDENCE: u8 = 7;
+
+fn precedence(node: &Formula) -> u8 {
+    match node {
+        Formula::Var(_) | Formula::Const(_) => ATOM_PRECEDENCE,
+        Formula::Not(_) => NOT_PRECEDENCE,
+        node => BinaryOp::of(node).unwrap().0.precedence(),
+    }
+}
+
+fn write_infix(node: &Formula, symbols: &Symbols, min_precedence: u8, out: &mut String) {
+    let parenthesize = precedence(node) < min_precedence;
+    if parenthesize {
+        out.push('(');
+    }
+    match node {
+        Formula::Var(name) => {
+            // The tokenizer prefers an operator at least as long as the name, so
+            // a name that starts any symbol, like `not` for `not `, is bracketed.
+            let is_operator = [
+                &symbols.not,
+                &symbols.and,
+                &symbols.or,
+                &symbols.xor,
+                &symbols.implies,
+                &symbols.equiv,
+            ]
+            .iter()
+            .any(|list| list.iter().any(|symbol| symbol.starts_with(name.as_str())));
+            if is_valid_name(name) && !is_operator {
+                out.push_str(name);
+            } else {
+                out.push_str(&format!("[{}]", name));
+            }
+        }
+        Formula::Const(val) => out.push(if *val { '1' } else { '0' }),
+        Formula::Not(inner) => {
+            let not = &symbols.not[0];
+            out.push_str(not);
+            if not.ends_with(|c: char| c.is_alphabetic()) {
+                out.push(' ');
+            }
+            write_infix(inner, symbols, NOT_PRECEDENCE, out);
+        }
+        node => {
+            let (op, left, right) = BinaryOp::of(node).unwrap();
+            let (left_min, right_min) = if op.right_associative() {
+                (op.precedence() + 1, op.precedence())
+            } else {
+                (op.precedence(), op.precedence() + 1)
+            };
+            write_infix(left, symbols, left_min, out);
+            out.push(' ');
+            out.push_str(op.symbol(symbols));
+            out.push(' ');
+            write_infix(right, symbols, right_min, out);
+        }
+    }
+    if parenthesize {
+        out.push(')');
+    }
+}
+
+pub fn ast_to_infix(node: &Formula) -> String {
+    ast_to_infix_with(node, &Symbols::ascii())
+}
+
+// Parsing the output with the same symbols always yields `node` again.
+pub fn ast_to_infix_with(node: &Formula, symbols: &Symbols) -> String {
+    let mut out = String::new();
+    write_infix(node, symbols, 0, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+
+    fn assert_same(infix: &str, rpn: &str) {
+        assert_eq!(infix_to_ast(infix), rpn_to_ast(rpn), "{}", infix);
+    }
+
+    #[test]
+    fn test_matches_rpn() {
+        assert_same("A", "A");
+        assert_same("!A", "A!");
+        assert_same("A & B | C", "AB&C|");
+        assert_same("A & (B | !C) -> D", "ABC!|&D>");
+        assert_same("(A = B) ^ 1", "AB=1^");
+        assert_same("!!(A|B)", "AB|!!");
+    }
+
+    #[test]
+    fn test_named_variables() {
+        assert_same("x_12 & req_valid | A", "[x_12][req_valid]&A|");
+        assert_same("!x1->[y_2]", "[x1]![y_2]>");
+        assert_same("AB ^ C", "[AB]C^");
+        assert_same("x_1∧x_2", "[x_1][x_2]&");
+
+        let keywords = Symbols {
+            not: to_strings(&["not"]),
+            and: to_strings(&["and"]),
+            or: to_strings(&["or"]),
+            xor: to_strings(&["xor"]),
+            implies: to_strings(&["implies"]),
+            equiv: to_strings(&["iff"]),
+        };
+        assert_eq!(
+            infix_to_ast_with("not android and order or [or]", &keywords),
+            rpn_to_ast("[android]![order]&[or]|")
+        );
+        let formula = rpn_to_ast("[or][x_1]|").unwrap();
+        assert_eq!(ast_to_infix_with(&formula, &keywords), "[or] or x_1");
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_same("A | B & C", "ABC&|");
+        assert_same("A ^ B & C | D", "ABC&^D|");
+        assert_same("A | B -> C", "AB|C>");
+        assert_same("A -> B <-> C", "AB>C=");
+        assert_same("!A & B", "A!B&");
+    }
+
+    #[test]
+    fn test_associativity() {
+        assert_same("A & B & C", "AB&C&");
+        assert_same("A | B | C", "AB|C|");
+        assert_same("A ^ B ^ C", "AB^C^");
+        assert_same("A <-> B <-> C", "AB=C=");
+        assert_same("A -> B -> C", "ABC>>");
+    }
+
+    #[test]
+    fn test_symbol_sets() {
+        assert_same("A ∧ (B ∨ ¬C) → D", "ABC!|&D>");
+        assert_same("A ⊕ B ↔ C", "AB^C=");
+        assert_same("A&&B||~C=>D<=>E", "AB&C!|D>E=");
+        assert_same("A&B>C=D", "AB&C>D=");
+
+        let keywords = keywords();
+        assert_eq!(
+            infix_to_ast_with("not A and B or C implies D", &keywords),
+            rpn_to_ast("A!B&C|D>")
+        );
+        assert_eq!(
+            infix_to_ast_with("A & B", &keywords),
+            Err(ParseError::UnknownSymbol {
+                position: 2,
+                symbol: '&'
+            })
+        );
+    }
+
+    fn keywords() -> Symbols {
+        Symbols {
+            not: to_strings(&["not "]),
+            and: to_strings(&["and"]),
+            or: to_strings(&["or"]),
+            xor: to_strings(&["xor"]),
+            implies: to_strings(&["implies"]),
+            equiv: to_strings(&["iff"]),
+        }
+    }
+
+    fn render(rpn: &str, symbols: &Symbols) -> String {
+        ast_to_infix_with(&rpn_to_ast(rpn).unwrap(), symbols)
+    }
+
+    #[test]
+    fn test_render_minimal_parentheses() {
+        let ascii = Symbols::ascii();
+        assert_eq!(render("AB&C|", &ascii), "A & B | C");
+        assert_eq!(render("ABC|&", &ascii), "A & (B | C)");
+        assert_eq!(render("AB&C&", &ascii), "A & B & C");
+        assert_eq!(render("ABC&&", &ascii), "A & (B & C)");
+        assert_eq!(render("ABC>>", &ascii), "A -> B -> C");
+        assert_eq!(render("AB>C>", &ascii), "(A -> B) -> C");
+        assert_eq!(render("AB|!", &ascii), "!(A | B)");
+        assert_eq!(render("A!!B^", &ascii), "!!A ^ B");
+        assert_eq!(render("A!B!C!&&", &ascii), "!A & (!B & !C)");
+        assert_eq!(render("AB=1^", &ascii), "(A <-> B) ^ 1");
+    }
+
+    #[test]
+    fn test_render_symbol_sets() {
+        let formula = "ABC!|&D>E=F^";
+        assert_eq!(
+            render(formula, &Symbols::ascii()),
+            "(A & (B | !C) -> D <-> E) ^ F"
+        );
+        assert_eq!(
+            render(formula, &Symbols::unicode()),
+            "(A ∧ (B ∨ ¬C) → D ↔ E) ⊕ F"
+        );
+        assert_eq!(
+            render(formula, &Symbols::latex()),
+            "(A \\land (B \\lor \\neg C) \\rightarrow D \\leftrightarrow E) \\oplus F"
+        );
+        assert_eq!(ast_to_infix(&rpn_to_ast("AB|!").unwrap()), "!(A | B)");
+    }
+
+    fn formulas_of_size(size: usize) -> Vec<Formula> {
+        let mut by_size: Vec<Vec<Formula>> = vec![vec![
+            Formula::var("A"),
+            Formula::var("x_12"),
+            Formula::Const(false),
+        ]];
+        for n in 1..=size {
+            let mut current: Vec<Formula> = by_size[n - 1]
+                .iter()
+                .map(|f| Formula::Not(Box::new(f.clone())))
+                .collect();
+            for left_size in 0..n {
+                for left in &by_size[left_size] {
+                    for right in &by_size[n - 1 - left_size] {
+                        for op in [
+                            BinaryOp::And,
+                            BinaryOp::Or,
+                            BinaryOp::Xor,
+                            BinaryOp::Implies,
+                            BinaryOp::Equiv,
+                        ] {
+                            current.push(op.build(left.clone(), right.clone()));
+                        }
+                    }
+                }
+            }
+            by_size.push(current);
+        }
+        by_size.concat()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sets = [Symbols::ascii(), Symbols::unicode(), Symbols::latex()];
+        for formula in formulas_of_size(3) {
+            for symbols in &sets {
+                let rendered = ast_to_infix_with(&formula, symbols);
+                assert_eq!(
+                    infix_to_ast_with(&rendered, symbols).as_ref(),
+                    Ok(&formula),
+                    "{}",
+                    rendered
+                );
+            }
+        }
+
+        let keywords = keywords();
+        let names = ["not", "and", "i", "nota", "B"].map(Formula::var);
+        for left in &names {
+            for right in &names {
+                for formula in [
+                    Formula::And(Box::new(left.clone()), Box::new(right.clone())),
+                    Formula::Not(Box::new(Formula::Or(
+                        Box::new(left.clone()),
+                        Box::new(right.clone()),
+                    ))),
+                ] {
+                    let rendered = ast_to_infix_with(&formula, &keywords);
+                    assert_eq!(
+                        infix_to_ast_with(&rendered, &keywords).as_ref(),
+                        Ok(&formula),
+                        "{}",
+                        rendered
+                    );
+                }
+            }
+        }
+        let formula = Formula::And(Box::new(names[0].clone()), Box::new(names[4].clone()));
+        assert_eq!(ast_to_infix_with(&formula, &keywords), "[not] and B");
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(infix_to_ast("   "), Err(ParseError::EmptyInput));
+        assert_eq!(
+            infix_to_ast("A & "),
+            Err(ParseError::UnexpectedEnd { position: 4 })
+        );
+        assert_eq!(
+            infix_to_ast("A & | B"),
+            Err(ParseError::UnexpectedSymbol {
+                position: 4,
+                symbol: '|'
+            })
+        );
+        assert_eq!(
+            infix_to_ast("(A & B"),
+            Err(ParseError::UnclosedParenthesis { position: 0 })
+        );
+        assert_eq!(
+            infix_to_ast("A & B)"),
+            Err(ParseError::UnexpectedSymbol {
+                position: 5,
+                symbol: ')'
+            })
+        );
+        assert_eq!(
+            infix_to_ast("A B"),
+            Err(ParseError::UnexpectedSymbol {
+                position: 2,
+                symbol: 'B'
+            })
+        );
+        assert_eq!(
+            infix_to_ast("A ∧ $"),
+            Err(ParseError::UnknownSymbol {
+                position: 4,
+                symbol: '$'
+            })
+        );
+        assert_eq!(
+            infix_to_ast("A & [x_1"),
+            Err(ParseError::UnclosedBracket { position: 4 })
+        );
+        assert_eq!(
+            infix_to_ast("A & [x-1]"),
+            Err(ParseError::UnknownSymbol {
+                position: 6,
+                symbol: '-'
+            })
+        );
+        assert_eq!(
+            infix_to_ast("[]"),
+            Err(ParseError::UnexpectedSymbol {
+                position: 1,
+                symbol: ']'
+            })
+        );
+        assert_eq!(infix_to_ast("[]"), rpn_to_ast("[]"));
+    }
+}
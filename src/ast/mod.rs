@@ -1,118 +1,451 @@
-use std::collections::VecDeque;
-
-#[derive(Debug, Clone)]
-pub enum ASTNode {
-    Const(char),
-    Not(Box<ASTNode>),
-    And(Box<ASTNode>, Box<ASTNode>),
-    Or(Box<ASTNode>, Box<ASTNode>),
-    Implies(Box<ASTNode>, Box<ASTNode>),
-    Equiv(Box<ASTNode>, Box<ASTNode>),
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use crate::algebra::{BooleanAlgebra, Bools};
+
+mod error;
+mod infix;
+mod variables;
+
+pub use error::ParseError;
+pub use infix::{ast_to_infix, ast_to_infix_with, infix_to_ast, infix_to_ast_with, Symbols};
+pub use variables::{is_valid_name, natural_cmp, VarTable};
+
+use variables::{is_identifier_char, is_identifier_start};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Formula {
+    Var(String),
+    Const(bool),
+    Not(Box<Formula>),
+    And(Box<Formula>, Box<Formula>),
+    Or(Box<Formula>, Box<Formula>),
+    Xor(Box<Formula>, Box<Formula>),
+    Implies(Box<Formula>, Box<Formula>),
+    Equiv(Box<Formula>, Box<Formula>),
+}
+
+impl Formula {
+    pub fn var(name: &str) -> Formula {
+        Formula::Var(name.to_string())
+    }
+
+    pub fn evaluate(&self, vars: &HashMap<String, bool>) -> bool {
+        self.evaluate_in(&Bools, vars)
+    }
+
+    // Evaluates in any Boolean algebra, unbound variables being its bottom.
+    pub fn evaluate_in<A: BooleanAlgebra>(
+        &self,
+        algebra: &A,
+        vars: &HashMap<String, A::Element>,
+    ) -> A::Element {
+        let eval = |node: &Formula| node.evaluate_in(algebra, vars);
+        match self {
+            Formula::Var(name) => vars.get(name).cloned().unwrap_or_else(|| algebra.bottom()),
+            Formula::Const(true) => algebra.top(),
+            Formula::Const(false) => algebra.bottom(),
+            Formula::Not(expr) => algebra.complement(&eval(expr)),
+            Formula::And(left, right) => algebra.meet(&eval(left), &eval(right)),
+            Formula::Or(left, right) => algebra.join(&eval(left), &eval(right)),
+            Formula::Xor(left, right) => algebra.xor(&eval(left), &eval(right)),
+            Formula::Implies(left, right) => algebra.implies(&eval(left), &eval(right)),
+            Formula::Equiv(left, right) => algebra.equiv(&eval(left), &eval(right)),
+        }
+    }
+
+    pub fn variables(&self) -> Vec<String> {
+        fn collect<'a>(node: &'a Formula, acc: &mut BTreeSet<&'a str>) {
+            match node {
+                Formula::Var(name) => {
+                    acc.insert(name);
+                }
+                Formula::Const(_) => {}
+                Formula::Not(expr) => collect(expr, acc),
+                Formula::And(left, right)
+                | Formula::Or(left, right)
+                | Formula::Xor(left, right)
+                | Formula::Implies(left, right)
+                | Formula::Equiv(left, right) => {
+                    collect(left, acc);
+                    collect(right, acc);
+                }
+            }
+        }
+
+        let mut acc = BTreeSet::new();
+        collect(self, &mut acc);
+        let mut names: Vec<String> = acc.into_iter().map(str::to_string).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+}
+
+fn read_bracketed_name(
+    open: usize,
+    chars: &mut impl Iterator<Item = (usize, char)>,
+) -> Result<String, ParseError> {
+    let mut name = String::new();
+    for (position, char) in chars {
+        match char {
+            ']' if is_valid_name(&name) => return Ok(name),
+            ']' => {
+                return Err(ParseError::UnexpectedSymbol {
+                    position,
+                    symbol: char,
+                })
+            }
+            c if is_identifier_start(c) || (!name.is_empty() && is_identifier_char(c)) => {
+                name.push(c)
+            }
+            _ => {
+                return Err(ParseError::UnknownSymbol {
+                    position,
+                    symbol: char,
+                })
+            }
+        }
+    }
+    Err(ParseError::UnclosedBracket { position: open })
 }
 
-pub fn rpn_to_ast(expression: &str) -> Option<ASTNode> {
-    let mut stack: VecDeque<ASTNode> = VecDeque::new();
+pub fn rpn_to_ast(expression: &str) -> Result<Formula, ParseError> {
+    let mut stack: VecDeque<(Formula, usize)> = VecDeque::new();
+
+    let mut chars = expression.chars().enumerate();
 
-    for char in expression.chars() {
+    while let Some((position, char)) = chars.next() {
         match char {
-            'A'..='Z' => stack.push_back(ASTNode::Const(char)),
+            'A'..='Z' => stack.push_back((Formula::Var(char.to_string()), position)),
+            '[' => {
+                let name = read_bracketed_name(position, &mut chars)?;
+                stack.push_back((Formula::Var(name), position));
+            }
+            '0' => stack.push_back((Formula::Const(false), position)),
+            '1' => stack.push_back((Formula::Const(true), position)),
             '!' => {
-                if let Some(expr) = stack.pop_back() {
-                    stack.push_back(ASTNode::Not(Box::new(expr)));
+                if let Some((expr, start)) = stack.pop_back() {
+                    stack.push_back((Formula::Not(Box::new(expr)), start));
                 } else {
-                    return None;
+                    return Err(ParseError::StackUnderflow {
+                        position,
+                        operator: char,
+                    });
                 }
             }
-            '&' | '|' | '>' | '=' => {
-                if let (Some(right), Some(left)) = (stack.pop_back(), stack.pop_back()) {
-                    let node = match char {
-                        '&' => ASTNode::And(Box::new(left), Box::new(right)),
-                        '|' => ASTNode::Or(Box::new(left), Box::new(right)),
-                        '>' => ASTNode::Implies(Box::new(left), Box::new(right)),
-                        '=' => ASTNode::Equiv(Box::new(left), Box::new(right)),
-                        _ => unreachable!(),
-                    };
-                    stack.push_back(node);
-                } else {
-                    return None;
+            '&' | '|' | '^' | '>' | '=' => {
+                if stack.len() < 2 {
+                    return Err(ParseError::StackUnderflow {
+                        position,
+                        operator: char,
+                    });
                 }
+                let (right, _) = stack.pop_back().unwrap();
+                let (left, start) = stack.pop_back().unwrap();
+                let node = match char {
+                    '&' => Formula::And(Box::new(left), Box::new(right)),
+                    '|' => Formula::Or(Box::new(left), Box::new(right)),
+                    '^' => Formula::Xor(Box::new(left), Box::new(right)),
+                    '>' => Formula::Implies(Box::new(left), Box::new(right)),
+                    '=' => Formula::Equiv(Box::new(left), Box::new(right)),
+                    _ => unreachable!(),
+                };
+                stack.push_back((node, start));
+            }
+            _ => {
+                return Err(ParseError::UnknownSymbol {
+                    position,
+                    symbol: char,
+                })
             }
-            _ => return None,
         }
     }
-    if stack.len() == 1 {
-        stack.pop_back()
-    } else {
-        None
+    match stack.len() {
+        0 => Err(ParseError::EmptyInput),
+        1 => Ok(stack.pop_back().unwrap().0),
+        count => {
+            let position = stack[1].1;
+            Err(ParseError::LeftoverOperands {
+                position,
+                symbol: expression.chars().nth(position).unwrap(),
+                count,
+            })
+        }
     }
 }
 
-pub fn to_nnf(node: ASTNode) -> ASTNode {
+fn equiv_to_nnf(left: Formula, right: Formula) -> Formula {
+    let left_and_right = Formula::And(Box::new(left.clone()), Box::new(right.clone()));
+    let not_left_and_not_right = Formula::And(
+        Box::new(Formula::Not(Box::new(left))),
+        Box::new(Formula::Not(Box::new(right))),
+    );
+    Formula::Or(
+        Box::new(to_nnf(left_and_right)),
+        Box::new(to_nnf(not_left_and_not_right)),
+    )
+}
+
+fn xor_to_nnf(left: Formula, right: Formula) -> Formula {
+    let left_and_not_right = Formula::And(
+        Box::new(left.clone()),
+        Box::new(Formula::Not(Box::new(right.clone()))),
+    );
+    let not_left_and_right = Formula::And(Box::new(Formula::Not(Box::new(left))), Box::new(right));
+    Formula::Or(
+        Box::new(to_nnf(left_and_not_right)),
+        Box::new(to_nnf(not_left_and_right)),
+    )
+}
+
+pub fn to_nnf(node: Formula) -> Formula {
     match node {
-        ASTNode::Not(boxed) => match *boxed {
-            ASTNode::Not(inner) => to_nnf(*inner),
-            ASTNode::And(left, right) => {
-                let left = ASTNode::Not(Box::new(*left));
-                let right = ASTNode::Not(Box::new(*right));
-                ASTNode::Or(Box::new(to_nnf(left)), Box::new(to_nnf(right)))
-            }
-            ASTNode::Or(left, right) => {
-                let left = ASTNode::Not(Box::new(*left));
-                let right = ASTNode::Not(Box::new(*right));
-                ASTNode::And(Box::new(to_nnf(left)), Box::new(to_nnf(right)))
+        Formula::Not(boxed) => match *boxed {
+            Formula::Not(inner) => to_nnf(*inner),
+            Formula::Const(val) => Formula::Const(!val),
+            Formula::Var(name) => Formula::Not(Box::new(Formula::Var(name))),
+            Formula::And(left, right) => {
+                let left = Formula::Not(Box::new(*left));
+                let right = Formula::Not(Box::new(*right));
+                Formula::Or(Box::new(to_nnf(left)), Box::new(to_nnf(right)))
             }
-            ASTNode::Implies(left, right) => {
-                let left = ASTNode::Not(Box::new(*left));
-                ASTNode::And(Box::new(to_nnf(left)), Box::new(to_nnf(*right)))
+            Formula::Or(left, right) => {
+                let left = Formula::Not(Box::new(*left));
+                let right = Formula::Not(Box::new(*right));
+                Formula::And(Box::new(to_nnf(left)), Box::new(to_nnf(right)))
             }
-            ASTNode::Equiv(left, right) => {
-                let left_and_right =
-                    ASTNode::And(Box::new(*left.clone()), Box::new(*right.clone()));
-                let not_left_and_not_right = ASTNode::And(
-                    Box::new(ASTNode::Not(Box::new(*left))),
-                    Box::new(ASTNode::Not(Box::new(*right))),
-                );
-                ASTNode::Or(
-                    Box::new(to_nnf(ASTNode::Not(Box::new(left_and_right)))),
-                    Box::new(to_nnf(ASTNode::Not(Box::new(not_left_and_not_right)))),
-                )
+            Formula::Implies(left, right) => {
+                let right = Formula::Not(Box::new(*right));
+                Formula::And(Box::new(to_nnf(*left)), Box::new(to_nnf(right)))
             }
-            ASTNode::Const(c) => ASTNode::Not(Box::new(ASTNode::Const(c))),
+            Formula::Xor(left, right) => equiv_to_nnf(*left, *right),
+            Formula::Equiv(left, right) => xor_to_nnf(*left, *right),
         },
-        ASTNode::And(left, right) => {
-            ASTNode::And(Box::new(to_nnf(*left)), Box::new(to_nnf(*right)))
-        }
-        ASTNode::Or(left, right) => ASTNode::Or(Box::new(to_nnf(*left)), Box::new(to_nnf(*right))),
-        ASTNode::Implies(left, right) => {
-            let left = ASTNode::Not(Box::new(*left));
-            ASTNode::Or(Box::new(to_nnf(left)), Box::new(to_nnf(*right)))
+        Formula::And(left, right) => {
+            Formula::And(Box::new(to_nnf(*left)), Box::new(to_nnf(*right)))
         }
-        ASTNode::Equiv(left, right) => {
-            let left_and_right = ASTNode::And(Box::new(*left.clone()), Box::new(*right.clone()));
-            let not_left_and_not_right = ASTNode::And(
-                Box::new(ASTNode::Not(Box::new(*left))),
-                Box::new(ASTNode::Not(Box::new(*right))),
-            );
-            ASTNode::Or(
-                Box::new(to_nnf(left_and_right)),
-                Box::new(to_nnf(not_left_and_not_right)),
-            )
+        Formula::Or(left, right) => Formula::Or(Box::new(to_nnf(*left)), Box::new(to_nnf(*right))),
+        Formula::Implies(left, right) => {
+            let left = Formula::Not(Box::new(*left));
+            Formula::Or(Box::new(to_nnf(left)), Box::new(to_nnf(*right)))
         }
+        Formula::Xor(left, right) => xor_to_nnf(*left, *right),
+        Formula::Equiv(left, right) => equiv_to_nnf(*left, *right),
         node => node,
     }
 }
 
-pub fn ast_to_string(node: ASTNode) -> String {
+pub fn ast_to_string(node: Formula) -> String {
     match node {
-        ASTNode::Const(c) => c.to_string(),
-        ASTNode::Not(expr) => format!("{}!", ast_to_string(*expr)),
-        ASTNode::And(left, right) => format!("{}{}&", ast_to_string(*left), ast_to_string(*right)),
-        ASTNode::Or(left, right) => format!("{}{}|", ast_to_string(*left), ast_to_string(*right)),
-        ASTNode::Implies(left, right) => {
+        Formula::Var(name)
+            if name.len() == 1 && name.starts_with(|c: char| c.is_ascii_uppercase()) =>
+        {
+            name
+        }
+        Formula::Var(name) => format!("[{}]", name),
+        Formula::Const(val) => if val { "1" } else { "0" }.to_string(),
+        Formula::Not(expr) => format!("{}!", ast_to_string(*expr)),
+        Formula::And(left, right) => format!("{}{}&", ast_to_string(*left), ast_to_string(*right)),
+        Formula::Or(left, right) => format!("{}{}|", ast_to_string(*left), ast_to_string(*right)),
+        Formula::Xor(left, right) => format!("{}{}^", ast_to_string(*left), ast_to_string(*right)),
+        Formula::Implies(left, right) => {
             format!("{}{}>", ast_to_string(*left), ast_to_string(*right))
         }
-        ASTNode::Equiv(left, right) => {
+        Formula::Equiv(left, right) => {
             format!("{}{}=", ast_to_string(*left), ast_to_string(*right))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::truth_table::equivalent;
+
+    #[test]
+    fn test_rpn_to_ast() {
+        let ast = rpn_to_ast("AB&C|").unwrap();
+        let expected_ast = Formula::Or(
+            Box::new(Formula::And(
+                Box::new(Formula::var("A")),
+                Box::new(Formula::var("B")),
+            )),
+            Box::new(Formula::var("C")),
+        );
+        assert_eq!(ast, expected_ast);
+    }
+
+    #[test]
+    fn test_rpn_to_ast_constants() {
+        let ast = rpn_to_ast("01^").unwrap();
+        let expected_ast = Formula::Xor(
+            Box::new(Formula::Const(false)),
+            Box::new(Formula::Const(true)),
+        );
+        assert_eq!(ast, expected_ast);
+    }
+
+    #[test]
+    fn test_rpn_to_ast_invalid() {
+        assert_eq!(rpn_to_ast(""), Err(ParseError::EmptyInput));
+        assert_eq!(
+            rpn_to_ast("AB!C"),
+            Err(ParseError::LeftoverOperands {
+                position: 1,
+                symbol: 'B',
+                count: 3
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("AB&C!&D"),
+            Err(ParseError::LeftoverOperands {
+                position: 6,
+                symbol: 'D',
+                count: 2
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("A&"),
+            Err(ParseError::StackUnderflow {
+                position: 1,
+                operator: '&'
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("!A"),
+            Err(ParseError::StackUnderflow {
+                position: 0,
+                operator: '!'
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("AB&c|"),
+            Err(ParseError::UnknownSymbol {
+                position: 3,
+                symbol: 'c'
+            })
+        );
+    }
+
+    fn assert_equivalent(left: &Formula, right: &Formula) {
+        assert!(
+            equivalent(left, right).unwrap(),
+            "{:?} <=> {:?}",
+            left,
+            right
+        );
+    }
+
+    fn is_nnf(node: &Formula) -> bool {
+        match node {
+            Formula::Var(_) | Formula::Const(_) => true,
+            Formula::Not(inner) => matches!(**inner, Formula::Var(_)),
+            Formula::And(left, right) | Formula::Or(left, right) => is_nnf(left) && is_nnf(right),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_to_nnf_xor() {
+        let nnf = to_nnf(rpn_to_ast("AB^").unwrap());
+        assert_eq!(ast_to_string(nnf), "AB!&A!B&|");
+        let nnf = to_nnf(rpn_to_ast("AB^!").unwrap());
+        assert_eq!(ast_to_string(nnf), "AB&A!B!&|");
+        let nnf = to_nnf(rpn_to_ast("AB=!").unwrap());
+        assert_eq!(ast_to_string(nnf), "AB!&A!B&|");
+    }
+
+    #[test]
+    fn test_to_nnf_preserves_semantics() {
+        for expression in [
+            "AB^", "AB^!", "AB=!", "AB>!", "AB^C^", "AB^C=!", "AB|C^!D>", "A1^", "A0=!", "AB&C^!!",
+        ] {
+            let ast = rpn_to_ast(expression).unwrap();
+            let nnf = to_nnf(ast.clone());
+            assert!(is_nnf(&nnf), "{} -> {:?}", expression, nnf);
+            assert_equivalent(&ast, &nnf);
+        }
+    }
+
+    #[test]
+    fn test_rpn_named_variables() {
+        let ast = rpn_to_ast("[x_12][req_valid]&A|").unwrap();
+        let expected_ast = Formula::Or(
+            Box::new(Formula::And(
+                Box::new(Formula::var("x_12")),
+                Box::new(Formula::var("req_valid")),
+            )),
+            Box::new(Formula::var("A")),
+        );
+        assert_eq!(ast, expected_ast);
+        assert_eq!(rpn_to_ast("[A]B&"), rpn_to_ast("AB&"));
+        assert_eq!(ast_to_string(ast), "[x_12][req_valid]&A|");
+    }
+
+    #[test]
+    fn test_rpn_named_variables_invalid() {
+        assert_eq!(
+            rpn_to_ast("A[x_1"),
+            Err(ParseError::UnclosedBracket { position: 1 })
+        );
+        assert_eq!(
+            rpn_to_ast("[x 1]"),
+            Err(ParseError::UnknownSymbol {
+                position: 2,
+                symbol: ' '
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("[1x]"),
+            Err(ParseError::UnknownSymbol {
+                position: 1,
+                symbol: '1'
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("A[]&"),
+            Err(ParseError::UnexpectedSymbol {
+                position: 2,
+                symbol: ']'
+            })
+        );
+        assert_eq!(
+            rpn_to_ast("[x_1]A[x_2]!"),
+            Err(ParseError::LeftoverOperands {
+                position: 5,
+                symbol: 'A',
+                count: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_variables_natural_order() {
+        let ast = rpn_to_ast("[x_10][x_2]&[x_1]|B^").unwrap();
+        assert_eq!(ast.variables(), vec!["B", "x_1", "x_2", "x_10"]);
+        let table = VarTable::from_formula(&ast);
+        assert_eq!(table.index_of("x_10"), Some(3));
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let err = rpn_to_ast("01&&").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "missing operand for operator '&' at offset 3"
+        );
+    }
+
+    #[test]
+    fn test_variables() {
+        let ast = rpn_to_ast("CA&B|A^1&").unwrap();
+        assert_eq!(ast.variables(), vec!["A", "B", "C"]);
+        assert!(rpn_to_ast("01&").unwrap().variables().is_empty());
+    }
+
+    #[test]
+    fn test_ast_to_string_round_trip() {
+        for expression in ["AB&C|", "01^!", "AB>C=", "A!!"] {
+            assert_eq!(ast_to_string(rpn_to_ast(expression).unwrap()), expression);
+        }
+    }
+}
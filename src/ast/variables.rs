@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use super::Formula;
+
+pub fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+pub fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_char)
+}
+
+// Orders embedded numbers by value, so that `x_2` comes before `x_10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+                let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+                let (a_num, b_num) = (
+                    a[..a_len].trim_start_matches('0'),
+                    b[..b_len].trim_start_matches('0'),
+                );
+                let ordering = a_num
+                    .len()
+                    .cmp(&b_num.len())
+                    .then_with(|| a_num.cmp(b_num))
+                    .then_with(|| a_len.cmp(&b_len));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VarTable {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl VarTable {
+    pub fn new() -> Self {
+        VarTable::default()
+    }
+
+    pub fn from_formula(formula: &Formula) -> Self {
+        let mut table = VarTable::new();
+        for name in formula.variables() {
+            table.insert(&name);
+        }
+        table
+    }
+
+    // Returns the index of `name`, appending it to the table if it is new.
+    pub fn insert(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_names() {
+        assert!(is_valid_name("A"));
+        assert!(is_valid_name("x_12"));
+        assert!(is_valid_name("_req_valid"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("12x"));
+        assert!(!is_valid_name("x-1"));
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["x_10", "x_2", "B", "x_1", "A", "x_02", "y"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["A", "B", "x_1", "x_2", "x_02", "x_10", "y"]);
+    }
+
+    #[test]
+    fn test_var_table() {
+        let mut table = VarTable::new();
+        assert_eq!(table.insert("req_valid"), 0);
+        assert_eq!(table.insert("x_1"), 1);
+        assert_eq!(table.insert("req_valid"), 0);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.index_of("x_1"), Some(1));
+        assert_eq!(table.index_of("x_2"), None);
+        assert_eq!(table.name(0), "req_valid");
+    }
+}
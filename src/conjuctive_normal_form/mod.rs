@@ -1,66 +1,417 @@
-use crate::ast::{ast_to_string, rpn_to_ast, to_nnf, ASTNode};
-
-fn distribute_or(node: ASTNode) -> ASTNode {
-    match node {
-        ASTNode::Or(left, right) => match (*left, *right) {
-            (ASTNode::And(left1, right1), right2) => {
-                let left_or = ASTNode::Or(Box::new(*left1), Box::new(right2.clone()));
-                let right_or = ASTNode::Or(Box::new(*right1), Box::new(right2));
-                ASTNode::And(
-                    Box::new(distribute_or(left_or)),
-                    Box::new(distribute_or(right_or)),
-                )
+use crate::ast::{ast_to_string, rpn_to_ast, to_nnf, Formula, ParseError, VarTable};
+
+mod simplify;
+mod tseitin;
+
+pub use simplify::{simplified_conjunctive_normal_form, SimplifyOptions, SimplifyReport};
+pub use tseitin::{to_cnf_tseitin, TseitinCnf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CnfMode {
+    // Logically equivalent CNF by distribution; may grow exponentially.
+    #[default]
+    Equivalent,
+    // Equisatisfiable CNF of linear size over additional `_t<n>` variables.
+    Tseitin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal {
+    pub var: usize,
+    pub negated: bool,
+}
+
+impl Literal {
+    pub fn new(var: usize, negated: bool) -> Self {
+        Literal { var, negated }
+    }
+
+    pub fn negate(self) -> Self {
+        Literal::new(self.var, !self.negated)
+    }
+
+    pub fn is_satisfied_by(self, value: bool) -> bool {
+        value != self.negated
+    }
+}
+
+pub type Clause = Vec<Literal>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cnf {
+    pub variables: VarTable,
+    pub clauses: Vec<Clause>,
+}
+
+// The connective joining the literals of a group: `Or` within a CNF clause,
+// `And` within a DNF term. The groups themselves are joined by the dual, so
+// distribution and the conversions below serve both normal forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Connective {
+    And,
+    Or,
+}
+
+impl Connective {
+    fn dual(self) -> Self {
+        match self {
+            Connective::And => Connective::Or,
+            Connective::Or => Connective::And,
+        }
+    }
+
+    // The constant that leaves a chain of this connective unchanged.
+    fn identity(self) -> bool {
+        self == Connective::And
+    }
+
+    fn constructor(self) -> fn(Box<Formula>, Box<Formula>) -> Formula {
+        match self {
+            Connective::And => Formula::And,
+            Connective::Or => Formula::Or,
+        }
+    }
+
+    fn join(self, left: Formula, right: Formula) -> Formula {
+        self.constructor()(Box::new(left), Box::new(right))
+    }
+
+    // The operands of `node` if this connective is its root, else `node` back.
+    fn operands(self, node: Formula) -> Result<(Formula, Formula), Formula> {
+        match (self, node) {
+            (Connective::And, Formula::And(left, right))
+            | (Connective::Or, Formula::Or(left, right)) => Ok((*left, *right)),
+            (_, node) => Err(node),
+        }
+    }
+
+    fn flatten(self, node: Formula, acc: &mut Vec<Formula>) {
+        match self.operands(node) {
+            Ok((left, right)) => {
+                self.flatten(left, acc);
+                self.flatten(right, acc);
             }
-            (left2, ASTNode::And(left3, right3)) => {
-                let left_or = ASTNode::Or(Box::new(left2.clone()), Box::new(*left3));
-                let right_or = ASTNode::Or(Box::new(left2), Box::new(*right3));
-                ASTNode::And(
-                    Box::new(distribute_or(left_or)),
-                    Box::new(distribute_or(right_or)),
-                )
+            Err(node) => acc.push(node),
+        }
+    }
+
+    fn fold(self, nodes: Vec<Formula>) -> Formula {
+        fold_right(nodes, self.constructor())
+    }
+}
+
+// Distributes `inner` over its dual at the root of `node`, whose operands are
+// already distributed.
+fn distribute_over(node: Formula, inner: Connective) -> Formula {
+    let outer = inner.dual();
+    match inner.operands(node) {
+        Ok((left, right)) => match outer.operands(left) {
+            Ok((left1, right1)) => outer.join(
+                distribute_over(inner.join(left1, right.clone()), inner),
+                distribute_over(inner.join(right1, right), inner),
+            ),
+            Err(left) => match outer.operands(right) {
+                Ok((left2, right2)) => outer.join(
+                    distribute_over(inner.join(left.clone(), left2), inner),
+                    distribute_over(inner.join(left, right2), inner),
+                ),
+                Err(right) => {
+                    inner.join(distribute_over(left, inner), distribute_over(right, inner))
+                }
+            },
+        },
+        Err(node) => match outer.operands(node) {
+            Ok((left, right)) => {
+                outer.join(distribute_over(left, inner), distribute_over(right, inner))
             }
-            (left, right) => ASTNode::Or(
-                Box::new(distribute_or(left)),
-                Box::new(distribute_or(right)),
+            Err(node) => node,
+        },
+    }
+}
+
+fn distribute(node: Formula, inner: Connective) -> Formula {
+    let outer = inner.dual();
+    match outer.operands(node) {
+        Ok((left, right)) => outer.join(distribute(left, inner), distribute(right, inner)),
+        Err(node) => match inner.operands(node) {
+            Ok((left, right)) => distribute_over(
+                inner.join(distribute(left, inner), distribute(right, inner)),
+                inner,
             ),
+            Err(node) => node,
         },
-        ASTNode::And(left, right) => ASTNode::And(
-            Box::new(distribute_or(*left)),
-            Box::new(distribute_or(*right)),
-        ),
-        node => node,
     }
 }
 
-fn to_cnf(node: ASTNode) -> ASTNode {
-    match node {
-        ASTNode::And(left, right) => {
-            ASTNode::And(Box::new(to_cnf(*left)), Box::new(to_cnf(*right)))
+pub(crate) fn fold_right(
+    mut nodes: Vec<Formula>,
+    op: fn(Box<Formula>, Box<Formula>) -> Formula,
+) -> Formula {
+    let mut acc = nodes.pop().expect("at least one operand");
+    while let Some(node) = nodes.pop() {
+        acc = op(Box::new(node), Box::new(acc));
+    }
+    acc
+}
+
+// Rebuilds the normal form with every connective chained to the right, so
+// that all operators of a group and all groups end up at the tail of the RPN.
+fn normalize(node: Formula, inner: Connective) -> Formula {
+    let outer = inner.dual();
+    let mut groups = Vec::new();
+    outer.flatten(node, &mut groups);
+    let groups = groups
+        .into_iter()
+        .map(|group| {
+            let mut literals = Vec::new();
+            inner.flatten(group, &mut literals);
+            inner.fold(literals)
+        })
+        .collect();
+    outer.fold(groups)
+}
+
+// The normal form made of `inner` groups of literals: CNF for `Or`, DNF for
+// `And`.
+pub(crate) fn normal_form(node: Formula, inner: Connective) -> Formula {
+    normalize(distribute(to_nnf(node), inner), inner)
+}
+
+pub fn to_cnf(node: Formula) -> Formula {
+    normal_form(node, Connective::Or)
+}
+
+// The groups of the normal form as literal lists. Identity constants are
+// dropped from a group, and a group holding the other constant is dropped.
+pub(crate) fn literal_groups(
+    formula: &Formula,
+    inner: Connective,
+) -> (VarTable, Vec<Vec<Literal>>) {
+    let mut variables = VarTable::from_formula(formula);
+    let mut nodes = Vec::new();
+    inner
+        .dual()
+        .flatten(normal_form(formula.clone(), inner), &mut nodes);
+
+    let mut groups = Vec::new();
+    'groups: for node in nodes {
+        let mut literals = Vec::new();
+        inner.flatten(node, &mut literals);
+        let mut group = Vec::new();
+        for literal in literals {
+            match literal {
+                Formula::Const(value) if value == inner.identity() => {}
+                Formula::Const(_) => continue 'groups,
+                Formula::Var(name) => group.push(Literal::new(variables.insert(&name), false)),
+                Formula::Not(negated) => match *negated {
+                    Formula::Var(name) => group.push(Literal::new(variables.insert(&name), true)),
+                    _ => unreachable!("normal form literals only negate variables"),
+                },
+                _ => unreachable!("normal form groups only contain literals"),
+            }
+        }
+        groups.push(group);
+    }
+    (variables, groups)
+}
+
+// An empty group is the identity of `inner`, no groups at all the identity of
+// its dual.
+pub(crate) fn groups_to_formula(
+    variables: &VarTable,
+    groups: &[Vec<Literal>],
+    inner: Connective,
+) -> Formula {
+    let literal_to_formula = |literal: &Literal| {
+        let var = Formula::var(variables.name(literal.var));
+        if literal.negated {
+            Formula::Not(Box::new(var))
+        } else {
+            var
         }
-        ASTNode::Or(left, right) => distribute_or(ASTNode::Or(
-            Box::new(to_cnf(*left)),
-            Box::new(to_cnf(*right)),
-        )),
-        node => node,
+    };
+    let groups: Vec<Formula> = groups
+        .iter()
+        .map(|group| {
+            if group.is_empty() {
+                Formula::Const(inner.identity())
+            } else {
+                inner.fold(group.iter().map(literal_to_formula).collect())
+            }
+        })
+        .collect();
+    let outer = inner.dual();
+    if groups.is_empty() {
+        Formula::Const(outer.identity())
+    } else {
+        outer.fold(groups)
+    }
+}
+
+impl Cnf {
+    pub fn from_formula(formula: &Formula) -> Self {
+        let (variables, clauses) = literal_groups(formula, Connective::Or);
+        Cnf { variables, clauses }
+    }
+
+    pub fn to_formula(&self) -> Formula {
+        groups_to_formula(&self.variables, &self.clauses, Connective::Or)
+    }
+
+    pub fn evaluate(&self, assignment: &[bool]) -> bool {
+        self.clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|literal| literal.is_satisfied_by(assignment[literal.var]))
+        })
     }
 }
 
-pub fn conjunctive_normal_form(expression: &str) -> String {
-    let ast = rpn_to_ast(expression).unwrap();
-    let nnf_ast = to_nnf(ast);
-    let cnf_ast = to_cnf(nnf_ast);
+// Test fixtures in DIMACS numbering: variable k is 1-based and a negative
+// number is its negation. Variables are named x_0, x_1 and so on.
+#[cfg(test)]
+pub(crate) fn lit(dimacs: i32) -> Literal {
+    Literal::new(dimacs.unsigned_abs() as usize - 1, dimacs < 0)
+}
 
-    ast_to_string(cnf_ast)
+#[cfg(test)]
+pub(crate) fn cnf(num_vars: usize, clauses: &[Vec<i32>]) -> Cnf {
+    let mut variables = VarTable::new();
+    for var in 0..num_vars {
+        variables.insert(&format!("x_{}", var));
+    }
+    Cnf {
+        variables,
+        clauses: clauses
+            .iter()
+            .map(|clause| clause.iter().map(|&l| lit(l)).collect())
+            .collect(),
+    }
+}
+
+pub fn conjunctive_normal_form(expression: &str) -> Result<String, ParseError> {
+    conjunctive_normal_form_with(expression, CnfMode::Equivalent)
+}
+
+pub fn conjunctive_normal_form_with(expression: &str, mode: CnfMode) -> Result<String, ParseError> {
+    let ast = rpn_to_ast(expression)?;
+    let cnf_ast = match mode {
+        CnfMode::Equivalent => to_cnf(ast),
+        CnfMode::Tseitin => to_cnf_tseitin(&ast).cnf.to_formula(),
+    };
+
+    Ok(ast_to_string(cnf_ast))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::truth_table::equivalent;
 
     #[test]
     fn test_cnf_conversion() {
         let expression = "AB|!C!&";
-        let cnf = conjunctive_normal_form(expression);
+        let cnf = conjunctive_normal_form(expression).unwrap();
         assert_eq!(cnf, "A!B!C!&&");
     }
+
+    #[test]
+    fn test_cnf_chains() {
+        assert_eq!(conjunctive_normal_form("AB&!").unwrap(), "A!B!|");
+        assert_eq!(conjunctive_normal_form("AB|C|D|").unwrap(), "ABCD|||");
+        assert_eq!(conjunctive_normal_form("AB&C&D&").unwrap(), "ABCD&&&");
+        assert_eq!(conjunctive_normal_form("AB&!C!|").unwrap(), "A!B!C!||");
+        assert_eq!(conjunctive_normal_form("AB&C|").unwrap(), "AC|BC|&");
+    }
+
+    fn is_clause(node: &Formula) -> bool {
+        match node {
+            Formula::Var(_) | Formula::Const(_) => true,
+            Formula::Not(inner) => matches!(**inner, Formula::Var(_)),
+            Formula::Or(left, right) => is_clause(left) && is_clause(right),
+            _ => false,
+        }
+    }
+
+    fn is_cnf(node: &Formula) -> bool {
+        match node {
+            Formula::And(left, right) => is_cnf(left) && is_cnf(right),
+            node => is_clause(node),
+        }
+    }
+
+    #[test]
+    fn test_cnf_xor() {
+        assert_eq!(
+            conjunctive_normal_form("AB^").unwrap(),
+            "AA!|AB|B!A!|B!B|&&&"
+        );
+        assert_eq!(
+            conjunctive_normal_form("AB^!").unwrap(),
+            "AA!|AB!|BA!|BB!|&&&"
+        );
+    }
+
+    #[test]
+    fn test_cnf_preserves_semantics() {
+        for expression in [
+            "AB^", "AB^C^", "AB=!", "AB^C|", "AB&C^D>", "AB>!C=", "AB|C&!D^",
+        ] {
+            let ast = rpn_to_ast(expression).unwrap();
+            let cnf = rpn_to_ast(&conjunctive_normal_form(expression).unwrap()).unwrap();
+            assert!(is_cnf(&cnf), "{} -> {:?}", expression, cnf);
+            assert!(equivalent(&ast, &cnf).unwrap(), "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_clauses() {
+        let cnf = Cnf::from_formula(&rpn_to_ast("AB|!C!&").unwrap());
+        assert_eq!(cnf.variables.names(), ["A", "B", "C"]);
+        assert_eq!(
+            cnf.clauses,
+            vec![
+                vec![Literal::new(0, true)],
+                vec![Literal::new(1, true)],
+                vec![Literal::new(2, true)],
+            ]
+        );
+        assert_eq!(ast_to_string(cnf.to_formula()), "A!B!C!&&");
+
+        let cnf = Cnf::from_formula(&rpn_to_ast("[x_1]1|[x_2]0|&").unwrap());
+        assert_eq!(cnf.clauses, vec![vec![Literal::new(1, false)]]);
+        assert!(cnf.evaluate(&[false, true]));
+        assert!(!cnf.evaluate(&[true, false]));
+
+        let cnf = Cnf::from_formula(&rpn_to_ast("A0&").unwrap());
+        assert_eq!(cnf.clauses, vec![vec![Literal::new(0, false)], vec![]]);
+        assert_eq!(cnf.to_formula(), rpn_to_ast("A0&").unwrap());
+        assert_eq!(
+            Cnf::from_formula(&rpn_to_ast("1").unwrap()).to_formula(),
+            Formula::Const(true)
+        );
+    }
+
+    #[test]
+    fn test_cnf_tseitin_mode() {
+        assert_eq!(
+            conjunctive_normal_form_with("AB&C|", CnfMode::Tseitin).unwrap(),
+            "[_t1]!A|[_t1]!B|[_t1]A!B!||[_t2][_t1]!|[_t2]C!|[_t2]![_t1]C||[_t2]&&&&&&"
+        );
+        assert_eq!(
+            conjunctive_normal_form_with("AB|!C!&", CnfMode::Equivalent),
+            conjunctive_normal_form("AB|!C!&")
+        );
+    }
+
+    #[test]
+    fn test_cnf_invalid() {
+        assert_eq!(
+            conjunctive_normal_form("AB&|"),
+            Err(ParseError::StackUnderflow {
+                position: 3,
+                operator: '|'
+            })
+        );
+    }
 }
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use super::{Clause, Cnf, Literal};
+use crate::ast::{ast_to_string, rpn_to_ast, ParseError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimplifyOptions {
+    // Propagates unit clauses into the others; the units themselves are kept,
+    // so the result stays equivalent to the input.
+    pub unit_propagation: bool,
+    // Drops every clause containing a pure literal; the result is only
+    // equisatisfiable with the input.
+    pub pure_literals: bool,
+}
+
+impl SimplifyOptions {
+    pub fn all() -> Self {
+        SimplifyOptions {
+            unit_propagation: true,
+            pure_literals: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimplifyReport {
+    pub tautologies: usize,
+    pub duplicate_literals: usize,
+    pub duplicate_clauses: usize,
+    pub subsumed_clauses: usize,
+    // Unit literals in the order they were propagated.
+    pub units: Vec<Literal>,
+    // Clauses satisfied by a unit, and falsified literals removed by one.
+    pub satisfied_clauses: usize,
+    pub falsified_literals: usize,
+    pub pure_literals: Vec<Literal>,
+    pub pure_clauses: usize,
+}
+
+impl SimplifyReport {
+    pub fn removed_clauses(&self) -> usize {
+        self.tautologies
+            + self.duplicate_clauses
+            + self.subsumed_clauses
+            + self.satisfied_clauses
+            + self.pure_clauses
+    }
+}
+
+// Drops repeated literals, keeping the first occurrence. Returns `None` for a
+// clause that contains both polarities of some variable.
+fn normalize_clause(clause: &Clause, report: &mut SimplifyReport) -> Option<Clause> {
+    let mut seen = HashSet::new();
+    let mut normalized = Clause::new();
+    for &literal in clause {
+        if seen.contains(&literal.negate()) {
+            return None;
+        }
+        if seen.insert(literal) {
+            normalized.push(literal);
+        } else {
+            report.duplicate_literals += 1;
+        }
+    }
+    Some(normalized)
+}
+
+fn propagate_units(clauses: &mut Vec<Clause>, report: &mut SimplifyReport) {
+    while !clauses.iter().any(|clause| clause.is_empty()) {
+        let Some(unit) = clauses
+            .iter()
+            .find(|clause| clause.len() == 1 && !report.units.contains(&clause[0]))
+            .map(|clause| clause[0])
+        else {
+            break;
+        };
+        report.units.push(unit);
+        clauses.retain_mut(|clause| {
+            if clause.len() > 1 && clause.contains(&unit) {
+                report.satisfied_clauses += 1;
+                return false;
+            }
+            let len = clause.len();
+            clause.retain(|&literal| literal != unit.negate());
+            report.falsified_literals += len - clause.len();
+            true
+        });
+    }
+}
+
+fn eliminate_pure_literals(
+    clauses: &mut Vec<Clause>,
+    num_vars: usize,
+    report: &mut SimplifyReport,
+) {
+    loop {
+        // Occurrences of each variable as [positive, negative].
+        let mut occurs = vec![[false; 2]; num_vars];
+        for literal in clauses.iter().flatten() {
+            occurs[literal.var][literal.negated as usize] = true;
+        }
+        let pure: Vec<Literal> = occurs
+            .iter()
+            .enumerate()
+            .filter(|(_, [positive, negative])| positive != negative)
+            .map(|(var, [_, negative])| Literal::new(var, *negative))
+            .collect();
+        if pure.is_empty() {
+            return;
+        }
+        let len = clauses.len();
+        clauses.retain(|clause| !clause.iter().any(|literal| pure.contains(literal)));
+        report.pure_clauses += len - clauses.len();
+        report.pure_literals.extend(pure);
+    }
+}
+
+// Removes every clause that is a superset of another one. Of several equal
+// clauses only the first is kept.
+fn remove_subsumed(clauses: &mut Vec<Clause>, report: &mut SimplifyReport) {
+    let sorted: Vec<Clause> = clauses
+        .iter()
+        .map(|clause| {
+            let mut sorted = clause.clone();
+            sorted.sort();
+            sorted
+        })
+        .collect();
+    let is_subset = |small: &Clause, large: &Clause| {
+        small
+            .iter()
+            .all(|literal| large.binary_search(literal).is_ok())
+    };
+
+    let mut index = 0;
+    clauses.retain(|_| {
+        let current = index;
+        index += 1;
+        let clause = &sorted[current];
+        for (other, candidate) in sorted.iter().enumerate() {
+            if other == current || candidate.len() > clause.len() {
+                continue;
+            }
+            if candidate.len() == clause.len() {
+                if other < current && candidate == clause {
+                    report.duplicate_clauses += 1;
+                    return false;
+                }
+            } else if is_subset(candidate, clause) {
+                report.subsumed_clauses += 1;
+                return false;
+            }
+        }
+        true
+    });
+}
+
+impl Cnf {
+    pub fn simplify(&mut self, options: SimplifyOptions) -> SimplifyReport {
+        let mut report = SimplifyReport::default();
+        let mut clauses: Vec<Clause> = Vec::new();
+        for clause in &self.clauses {
+            match normalize_clause(clause, &mut report) {
+                Some(clause) => clauses.push(clause),
+                None => report.tautologies += 1,
+            }
+        }
+        if options.unit_propagation {
+            propagate_units(&mut clauses, &mut report);
+        }
+        if options.pure_literals {
+            eliminate_pure_literals(&mut clauses, self.variables.len(), &mut report);
+        }
+        remove_subsumed(&mut clauses, &mut report);
+        self.clauses = clauses;
+        report
+    }
+}
+
+pub fn simplified_conjunctive_normal_form(
+    expression: &str,
+    options: SimplifyOptions,
+) -> Result<(String, SimplifyReport), ParseError> {
+    let mut cnf = Cnf::from_formula(&rpn_to_ast(expression)?);
+    let report = cnf.simplify(options);
+    Ok((ast_to_string(cnf.to_formula()), report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conjuctive_normal_form::cnf;
+    use crate::sat::solve_cnf;
+
+    fn simplify(expression: &str, options: SimplifyOptions) -> String {
+        simplified_conjunctive_normal_form(expression, options)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_basic_simplification() {
+        let options = SimplifyOptions::default();
+        assert_eq!(simplify("AA|", options), "A");
+        assert_eq!(simplify("AA!|B|", options), "1");
+        assert_eq!(simplify("AB|A&", options), "A");
+        assert_eq!(simplify("AB|BA|&", options), "AB|");
+        assert_eq!(simplify("AB^", options), "AB|B!A!|&");
+        assert_eq!(simplify("A0&", options), "0");
+    }
+
+    #[test]
+    fn test_report() {
+        let mut formula = cnf(
+            3,
+            &[
+                vec![1, -1, 2],
+                vec![1, 1, 2],
+                vec![2, 1],
+                vec![1, 2, 3],
+                vec![3],
+            ],
+        );
+        let report = formula.simplify(SimplifyOptions::default());
+        assert_eq!(
+            formula.clauses,
+            cnf(3, &[vec![1, 2], vec![3]]).clauses,
+            "{:?}",
+            report
+        );
+        assert_eq!(report.tautologies, 1);
+        assert_eq!(report.duplicate_literals, 1);
+        assert_eq!(report.duplicate_clauses, 1);
+        assert_eq!(report.subsumed_clauses, 1);
+        assert_eq!(report.removed_clauses(), 3);
+    }
+
+    #[test]
+    fn test_unit_propagation() {
+        let mut formula = cnf(3, &[vec![1], vec![-1, 2], vec![-2, 3, 1], vec![-3, -2]]);
+        let report = formula.simplify(SimplifyOptions {
+            unit_propagation: true,
+            ..SimplifyOptions::default()
+        });
+        assert_eq!(
+            formula.clauses,
+            cnf(3, &[vec![1], vec![2], vec![-3]]).clauses
+        );
+        assert_eq!(
+            report.units,
+            vec![
+                Literal::new(0, false),
+                Literal::new(1, false),
+                Literal::new(2, true)
+            ]
+        );
+        assert_eq!(report.satisfied_clauses, 1);
+        assert_eq!(report.falsified_literals, 2);
+
+        let mut formula = cnf(2, &[vec![1], vec![-1, 2], vec![-2], vec![1, 2]]);
+        formula.simplify(SimplifyOptions::all());
+        assert_eq!(formula.clauses, vec![vec![]]);
+    }
+
+    #[test]
+    fn test_pure_literals() {
+        let mut formula = cnf(3, &[vec![1, 2], vec![1, -2], vec![-2, 3], vec![2, -3]]);
+        let report = formula.simplify(SimplifyOptions {
+            pure_literals: true,
+            ..SimplifyOptions::default()
+        });
+        assert_eq!(report.pure_literals, vec![Literal::new(0, false)]);
+        assert_eq!(report.pure_clauses, 2);
+        assert_eq!(formula.clauses, cnf(3, &[vec![-2, 3], vec![2, -3]]).clauses);
+    }
+
+    #[test]
+    fn test_preserves_semantics() {
+        for expression in ["AB^C^", "AB=!C|", "AB>C&D=", "AB|C!&D^", "AB&C|A&", "AB^A&"] {
+            let original = Cnf::from_formula(&rpn_to_ast(expression).unwrap());
+            let mut equivalent = original.clone();
+            equivalent.simplify(SimplifyOptions {
+                unit_propagation: true,
+                ..SimplifyOptions::default()
+            });
+            let n = original.variables.len();
+            for mask in 0..1u32 << n {
+                let assignment: Vec<bool> = (0..n).map(|i| mask >> i & 1 == 1).collect();
+                assert_eq!(
+                    original.evaluate(&assignment),
+                    equivalent.evaluate(&assignment),
+                    "{}",
+                    expression
+                );
+            }
+
+            let mut equisatisfiable = original.clone();
+            equisatisfiable.simplify(SimplifyOptions::all());
+            assert_eq!(
+                solve_cnf(&original).is_some(),
+                solve_cnf(&equisatisfiable).is_some(),
+                "{}",
+                expression
+            );
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use super::{Clause, Cnf, Literal};
+use crate::ast::{Formula, VarTable};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TseitinCnf {
+    pub cnf: Cnf,
+    // Variables below this index are the ones of the original formula.
+    pub original_vars: usize,
+    // Each auxiliary variable with the gate it stands for, over earlier variables.
+    pub definitions: Vec<(usize, Formula)>,
+}
+
+impl TseitinCnf {
+    pub fn definition(&self, var: usize) -> Option<&Formula> {
+        self.definitions
+            .iter()
+            .find(|(aux, _)| *aux == var)
+            .map(|(_, gate)| gate)
+    }
+
+    // Expands an auxiliary variable back into the original sub-formula it encodes.
+    pub fn subformula(&self, var: usize) -> Option<Formula> {
+        self.definition(var).map(|gate| self.expand(gate))
+    }
+
+    fn expand(&self, node: &Formula) -> Formula {
+        match node {
+            Formula::Var(name) => match self.cnf.variables.index_of(name) {
+                Some(var) if var >= self.original_vars => self.subformula(var).unwrap(),
+                _ => node.clone(),
+            },
+            Formula::Const(_) => node.clone(),
+            Formula::Not(inner) => Formula::Not(Box::new(self.expand(inner))),
+            Formula::And(left, right) => {
+                Formula::And(Box::new(self.expand(left)), Box::new(self.expand(right)))
+            }
+            Formula::Or(left, right) => {
+                Formula::Or(Box::new(self.expand(left)), Box::new(self.expand(right)))
+            }
+            Formula::Xor(left, right) => {
+                Formula::Xor(Box::new(self.expand(left)), Box::new(self.expand(right)))
+            }
+            Formula::Implies(left, right) => {
+                Formula::Implies(Box::new(self.expand(left)), Box::new(self.expand(right)))
+            }
+            Formula::Equiv(left, right) => {
+                Formula::Equiv(Box::new(self.expand(left)), Box::new(self.expand(right)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Term {
+    Lit(Literal),
+    Const(bool),
+}
+
+impl Term {
+    fn negate(self) -> Term {
+        match self {
+            Term::Lit(literal) => Term::Lit(literal.negate()),
+            Term::Const(val) => Term::Const(!val),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Gate {
+    And,
+    Or,
+    Xor,
+}
+
+struct Encoder {
+    variables: VarTable,
+    clauses: Vec<Clause>,
+    definitions: Vec<(usize, Formula)>,
+    gates: HashMap<(Gate, Literal, Literal), Literal>,
+    next_aux: usize,
+}
+
+impl Encoder {
+    fn fresh(&mut self) -> usize {
+        loop {
+            self.next_aux += 1;
+            let name = format!("_t{}", self.next_aux);
+            if self.variables.index_of(&name).is_none() {
+                return self.variables.insert(&name);
+            }
+        }
+    }
+
+    fn literal_formula(&self, literal: Literal) -> Formula {
+        let var = Formula::var(self.variables.name(literal.var));
+        if literal.negated {
+            Formula::Not(Box::new(var))
+        } else {
+            var
+        }
+    }
+
+    fn gate(&mut self, gate: Gate, a: Term, b: Term) -> Term {
+        let (a, b) = match (gate, a, b) {
+            (Gate::And, Term::Const(false), _) | (Gate::And, _, Term::Const(false)) => {
+                return Term::Const(false)
+            }
+            (Gate::Or, Term::Const(true), _) | (Gate::Or, _, Term::Const(true)) => {
+                return Term::Const(true)
+            }
+            (Gate::And, Term::Const(true), other)
+            | (Gate::And, other, Term::Const(true))
+            | (Gate::Or, Term::Const(false), other)
+            | (Gate::Or, other, Term::Const(false))
+            | (Gate::Xor, Term::Const(false), other)
+            | (Gate::Xor, other, Term::Const(false)) => return other,
+            (Gate::Xor, Term::Const(true), other) | (Gate::Xor, other, Term::Const(true)) => {
+                return other.negate()
+            }
+            (_, Term::Lit(a), Term::Lit(b)) => (a, b),
+        };
+        let key = (gate, a.min(b), a.max(b));
+        if let Some(&output) = self.gates.get(&key) {
+            return Term::Lit(output);
+        }
+
+        let output = Literal::new(self.fresh(), false);
+        let g = output;
+        let clauses = match gate {
+            Gate::And => vec![
+                vec![g.negate(), a],
+                vec![g.negate(), b],
+                vec![g, a.negate(), b.negate()],
+            ],
+            Gate::Or => vec![
+                vec![g, a.negate()],
+                vec![g, b.negate()],
+                vec![g.negate(), a, b],
+            ],
+            Gate::Xor => vec![
+                vec![g.negate(), a, b],
+                vec![g.negate(), a.negate(), b.negate()],
+                vec![g, a.negate(), b],
+                vec![g, a, b.negate()],
+            ],
+        };
+        self.clauses.extend(clauses);
+
+        let (left, right) = (
+            Box::new(self.literal_formula(a)),
+            Box::new(self.literal_formula(b)),
+        );
+        let definition = match gate {
+            Gate::And => Formula::And(left, right),
+            Gate::Or => Formula::Or(left, right),
+            Gate::Xor => Formula::Xor(left, right),
+        };
+        self.definitions.push((output.var, definition));
+        self.gates.insert(key, output);
+        Term::Lit(output)
+    }
+
+    fn encode(&mut self, node: &Formula) -> Term {
+        match node {
+            Formula::Var(name) => Term::Lit(Literal::new(self.variables.insert(name), false)),
+            Formula::Const(val) => Term::Const(*val),
+            Formula::Not(inner) => self.encode(inner).negate(),
+            Formula::And(left, right) => {
+                let (a, b) = (self.encode(left), self.encode(right));
+                self.gate(Gate::And, a, b)
+            }
+            Formula::Or(left, right) => {
+                let (a, b) = (self.encode(left), self.encode(right));
+                self.gate(Gate::Or, a, b)
+            }
+            Formula::Xor(left, right) => {
+                let (a, b) = (self.encode(left), self.encode(right));
+                self.gate(Gate::Xor, a, b)
+            }
+            Formula::Implies(left, right) => {
+                let (a, b) = (self.encode(left), self.encode(right));
+                self.gate(Gate::Or, a.negate(), b)
+            }
+            Formula::Equiv(left, right) => {
+                let (a, b) = (self.encode(left), self.encode(right));
+                self.gate(Gate::Xor, a, b).negate()
+            }
+        }
+    }
+}
+
+pub fn to_cnf_tseitin(formula: &Formula) -> TseitinCnf {
+    let variables = VarTable::from_formula(formula);
+    let original_vars = variables.len();
+    let mut encoder = Encoder {
+        variables,
+        clauses: Vec::new(),
+        definitions: Vec::new(),
+        gates: HashMap::new(),
+        next_aux: 0,
+    };
+    match encoder.encode(formula) {
+        Term::Lit(root) => encoder.clauses.push(vec![root]),
+        Term::Const(true) => {}
+        Term::Const(false) => encoder.clauses.push(vec![]),
+    }
+    TseitinCnf {
+        cnf: Cnf {
+            variables: encoder.variables,
+            clauses: encoder.clauses,
+        },
+        original_vars,
+        definitions: encoder.definitions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+    use crate::sat::Solver;
+    use crate::truth_table::assignments;
+
+    // Every model of the formula extends to exactly one model of the encoding.
+    fn assert_equisatisfiable(expression: &str) {
+        let formula = rpn_to_ast(expression).unwrap();
+        let tseitin = to_cnf_tseitin(&formula);
+        let names = &tseitin.cnf.variables.names()[..tseitin.original_vars];
+        for vars in assignments(names) {
+            let mut solver = Solver::from_cnf(&tseitin.cnf);
+            for (i, name) in names.iter().enumerate() {
+                solver.add_clause(&[Literal::new(i, !vars[name])]);
+            }
+            assert_eq!(
+                solver.solve().is_some(),
+                formula.evaluate(&vars),
+                "{} {:?}",
+                expression,
+                vars
+            );
+        }
+    }
+
+    #[test]
+    fn test_equisatisfiable() {
+        for expression in [
+            "AB&",
+            "AB|C&",
+            "AB^",
+            "AB=",
+            "AB>",
+            "AB>!",
+            "AB&C|!D^",
+            "AA!&",
+            "A1&",
+            "A0|B^",
+            "1",
+            "0",
+            "A!",
+            "AB=C=D=",
+            "[x_1][x_2]&[x_1][x_2]&|",
+        ] {
+            assert_equisatisfiable(expression);
+        }
+    }
+
+    #[test]
+    fn test_linear_size() {
+        let pairs = 12;
+        let mut expression = String::new();
+        for i in 0..pairs {
+            expression.push_str(&format!("[a_{}][b_{}]&", i, i));
+            if i > 0 {
+                expression.push('|');
+            }
+        }
+        let tseitin = to_cnf_tseitin(&rpn_to_ast(&expression).unwrap());
+        assert_eq!(tseitin.original_vars, 2 * pairs);
+        assert_eq!(tseitin.cnf.variables.len(), 2 * pairs + 2 * pairs - 1);
+        assert_eq!(tseitin.cnf.clauses.len(), 3 * (2 * pairs - 1) + 1);
+    }
+
+    #[test]
+    fn test_definitions_map_back() {
+        let formula = rpn_to_ast("AB&C|").unwrap();
+        let tseitin = to_cnf_tseitin(&formula);
+        assert_eq!(tseitin.cnf.variables.names(), ["A", "B", "C", "_t1", "_t2"]);
+        assert_eq!(tseitin.definition(3), Some(&rpn_to_ast("AB&").unwrap()));
+        assert_eq!(
+            tseitin.definition(4),
+            Some(&Formula::Or(
+                Box::new(Formula::var("_t1")),
+                Box::new(Formula::var("C")),
+            ))
+        );
+        assert_eq!(tseitin.subformula(4), Some(formula));
+        assert_eq!(tseitin.subformula(0), None);
+    }
+
+    #[test]
+    fn test_shared_subformulas() {
+        let tseitin = to_cnf_tseitin(&rpn_to_ast("AB&C|AB&C|!&").unwrap());
+        assert_eq!(tseitin.definitions.len(), 3);
+    }
+
+    #[test]
+    fn test_fresh_names_avoid_collisions() {
+        let tseitin = to_cnf_tseitin(&rpn_to_ast("[_t1]B&").unwrap());
+        assert_eq!(tseitin.cnf.variables.names(), ["B", "_t1", "_t2"]);
+    }
+}
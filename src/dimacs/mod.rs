@@ -0,0 +1,266 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::ast::{is_valid_name, rpn_to_ast, ParseError, VarTable};
+use crate::conjuctive_normal_form::{Clause, Cnf, Literal};
+
+#[derive(Debug)]
+pub enum DimacsError {
+    Io(io::Error),
+    MissingHeader,
+    InvalidHeader { line: usize },
+    InvalidLiteral { line: usize, token: String },
+    VariableOutOfRange { line: usize, var: usize },
+    UnterminatedClause,
+    ClauseCountMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::Io(err) => write!(f, "{}", err),
+            DimacsError::MissingHeader => write!(f, "missing 'p cnf' header"),
+            DimacsError::InvalidHeader { line } => write!(f, "invalid header on line {}", line),
+            DimacsError::InvalidLiteral { line, token } => {
+                write!(f, "invalid literal '{}' on line {}", token, line)
+            }
+            DimacsError::VariableOutOfRange { line, var } => {
+                write!(f, "variable {} on line {} exceeds the header", var, line)
+            }
+            DimacsError::UnterminatedClause => write!(f, "last clause is not terminated by 0"),
+            DimacsError::ClauseCountMismatch { expected, found } => {
+                write!(f, "header announces {} clauses, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Error for DimacsError {}
+
+impl From<io::Error> for DimacsError {
+    fn from(err: io::Error) -> Self {
+        DimacsError::Io(err)
+    }
+}
+
+// Comment line emitted by `write_dimacs` to keep the original variable names.
+fn parse_mapping(comment: &str) -> Option<(usize, &str)> {
+    let mut tokens = comment.split_whitespace();
+    match (
+        tokens.next(),
+        tokens.next(),
+        tokens.next(),
+        tokens.next(),
+        tokens.next(),
+    ) {
+        (Some("var"), Some(index), Some("="), Some(name), None) if is_valid_name(name) => {
+            Some((index.parse().ok()?, name))
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_dimacs(input: &str) -> Result<Cnf, DimacsError> {
+    let mut names: Vec<(usize, String)> = Vec::new();
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses = Vec::new();
+    let mut clause = Clause::new();
+
+    for (number, line) in input.lines().enumerate() {
+        let number = number + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('c') {
+            if let Some((index, name)) = parse_mapping(comment) {
+                names.push((index, name.to_string()));
+            }
+            continue;
+        }
+        // SATLIB benchmarks end with a '%' line followed by garbage.
+        if line.starts_with('%') {
+            break;
+        }
+        if line.starts_with('p') {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match (header, tokens.as_slice()) {
+                (None, ["p", "cnf", vars, count]) => match (vars.parse(), count.parse()) {
+                    (Ok(vars), Ok(count)) => header = Some((vars, count)),
+                    _ => return Err(DimacsError::InvalidHeader { line: number }),
+                },
+                _ => return Err(DimacsError::InvalidHeader { line: number }),
+            }
+            continue;
+        }
+
+        let Some((num_vars, _)) = header else {
+            return Err(DimacsError::MissingHeader);
+        };
+        for token in line.split_whitespace() {
+            let value: i64 = token.parse().map_err(|_| DimacsError::InvalidLiteral {
+                line: number,
+                token: token.to_string(),
+            })?;
+            if value == 0 {
+                clauses.push(std::mem::take(&mut clause));
+                continue;
+            }
+            let var = value.unsigned_abs() as usize;
+            if var > num_vars {
+                return Err(DimacsError::VariableOutOfRange { line: number, var });
+            }
+            clause.push(Literal::new(var - 1, value < 0));
+        }
+    }
+
+    let Some((num_vars, count)) = header else {
+        return Err(DimacsError::MissingHeader);
+    };
+    if !clause.is_empty() {
+        return Err(DimacsError::UnterminatedClause);
+    }
+    if clauses.len() != count {
+        return Err(DimacsError::ClauseCountMismatch {
+            expected: count,
+            found: clauses.len(),
+        });
+    }
+
+    let mut variables = VarTable::new();
+    for var in 1..=num_vars {
+        let name = names
+            .iter()
+            .find(|(index, name)| *index == var && variables.index_of(name).is_none())
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| format!("x_{}", var));
+        variables.insert(&name);
+    }
+    if variables.len() != num_vars {
+        // A mapped name collided with a generated one; fall back to plain indices.
+        variables = VarTable::new();
+        for var in 1..=num_vars {
+            variables.insert(&format!("x_{}", var));
+        }
+    }
+    Ok(Cnf { variables, clauses })
+}
+
+pub fn read_dimacs<R: Read>(mut reader: R) -> Result<Cnf, DimacsError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    parse_dimacs(&input)
+}
+
+pub fn write_dimacs<W: Write>(cnf: &Cnf, out: &mut W) -> io::Result<()> {
+    for (index, name) in cnf.variables.names().iter().enumerate() {
+        writeln!(out, "c var {} = {}", index + 1, name)?;
+    }
+    writeln!(out, "p cnf {} {}", cnf.variables.len(), cnf.clauses.len())?;
+    for clause in &cnf.clauses {
+        for literal in clause {
+            let var = literal.var as i64 + 1;
+            write!(out, "{} ", if literal.negated { -var } else { var })?;
+        }
+        writeln!(out, "0")?;
+    }
+    Ok(())
+}
+
+pub fn cnf_to_dimacs(cnf: &Cnf) -> String {
+    let mut out = Vec::new();
+    write_dimacs(cnf, &mut out).expect("writing to a Vec cannot fail");
+    String::from_utf8(out).expect("DIMACS output is ASCII")
+}
+
+pub fn expression_to_dimacs(expression: &str) -> Result<String, ParseError> {
+    let cnf = Cnf::from_formula(&rpn_to_ast(expression)?);
+    Ok(cnf_to_dimacs(&cnf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sat::solve_cnf;
+
+    #[test]
+    fn test_write() {
+        assert_eq!(
+            expression_to_dimacs("AB|!C!&").unwrap(),
+            "c var 1 = A\nc var 2 = B\nc var 3 = C\np cnf 3 3\n-1 0\n-2 0\n-3 0\n"
+        );
+        assert_eq!(
+            expression_to_dimacs("[x_1][req]>[x_1]|").unwrap(),
+            "c var 1 = req\nc var 2 = x_1\np cnf 2 1\n-2 1 2 0\n"
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let input = "c simple example\np cnf 3 2\n1 -3 0\n2 3\n-1 0\n";
+        let cnf = parse_dimacs(input).unwrap();
+        assert_eq!(cnf.variables.names(), ["x_1", "x_2", "x_3"]);
+        assert_eq!(
+            cnf.clauses,
+            vec![
+                vec![Literal::new(0, false), Literal::new(2, true)],
+                vec![
+                    Literal::new(1, false),
+                    Literal::new(2, false),
+                    Literal::new(0, true)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_satlib_trailer() {
+        let input = "p cnf 2 2\n 1 2 0\n-1 0\n%\n0\n\n";
+        let cnf = parse_dimacs(input).unwrap();
+        assert_eq!(cnf.clauses.len(), 2);
+        assert_eq!(solve_cnf(&cnf), Some(vec![false, true]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for expression in ["AB|!C!&", "AB^C|", "[x_10][x_2]>[x_1]&", "AB=!C^"] {
+            let cnf = Cnf::from_formula(&rpn_to_ast(expression).unwrap());
+            let parsed = parse_dimacs(&cnf_to_dimacs(&cnf)).unwrap();
+            assert_eq!(parsed, cnf, "{}", expression);
+        }
+        let cnf = read_dimacs("p cnf 2 1\n1 -2 0\n".as_bytes()).unwrap();
+        assert_eq!(parse_dimacs(&cnf_to_dimacs(&cnf)).unwrap(), cnf);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            parse_dimacs("1 2 0\n"),
+            Err(DimacsError::MissingHeader)
+        ));
+        assert!(matches!(
+            parse_dimacs("p cnf two 1\n1 0\n"),
+            Err(DimacsError::InvalidHeader { line: 1 })
+        ));
+        assert!(matches!(
+            parse_dimacs("p cnf 2 1\n1 x 0\n"),
+            Err(DimacsError::InvalidLiteral { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_dimacs("p cnf 2 1\n1 3 0\n"),
+            Err(DimacsError::VariableOutOfRange { line: 2, var: 3 })
+        ));
+        assert!(matches!(
+            parse_dimacs("p cnf 2 1\n1 2\n"),
+            Err(DimacsError::UnterminatedClause)
+        ));
+        assert!(matches!(
+            parse_dimacs("p cnf 2 2\n1 2 0\n"),
+            Err(DimacsError::ClauseCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::ast::{ast_to_string, rpn_to_ast, Formula, ParseError, VarTable};
+use crate::conjuctive_normal_form::{
+    groups_to_formula, literal_groups, normal_form, Connective, Literal,
+};
+use crate::truth_table::{Rows, TruthTableError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DnfMode {
+    // Distribution of conjunctions over disjunctions in the NNF.
+    #[default]
+    Distributed,
+    // Sum of minterms, one term per true row of the truth table.
+    Canonical,
+}
+
+pub type Term = Vec<Literal>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dnf {
+    pub variables: VarTable,
+    pub terms: Vec<Term>,
+}
+
+pub fn to_dnf(node: Formula) -> Formula {
+    normal_form(node, Connective::And)
+}
+
+impl Dnf {
+    pub fn from_formula(formula: &Formula) -> Self {
+        let (variables, terms) = literal_groups(formula, Connective::And);
+        Dnf { variables, terms }
+    }
+
+    pub fn canonical(formula: &Formula) -> Result<Self, TruthTableError> {
+        let variables = VarTable::from_formula(formula);
+        let rows = Rows::new(formula)?;
+        let terms = rows
+            .clone()
+            .filter(|&(_, output)| output)
+            .map(|(mask, _)| {
+                (0..variables.len())
+                    .map(|var| Literal::new(var, !rows.input(mask, var)))
+                    .collect()
+            })
+            .collect();
+        Ok(Dnf { variables, terms })
+    }
+
+    pub fn to_formula(&self) -> Formula {
+        groups_to_formula(&self.variables, &self.terms, Connective::And)
+    }
+
+    pub fn evaluate(&self, assignment: &[bool]) -> bool {
+        self.terms.iter().any(|term| {
+            term.iter()
+                .all(|literal| literal.is_satisfied_by(assignment[literal.var]))
+        })
+    }
+}
+
+pub fn disjunctive_normal_form(expression: &str) -> Result<String, ParseError> {
+    Ok(ast_to_string(to_dnf(rpn_to_ast(expression)?)))
+}
+
+// Only the canonical form needs a truth table, and with it the variable limit.
+pub fn disjunctive_normal_form_with(
+    expression: &str,
+    mode: DnfMode,
+) -> Result<String, TruthTableError> {
+    let ast = rpn_to_ast(expression)?;
+    let dnf_ast = match mode {
+        DnfMode::Distributed => to_dnf(ast),
+        DnfMode::Canonical => Dnf::canonical(&ast)?.to_formula(),
+    };
+
+    Ok(ast_to_string(dnf_ast))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::truth_table::equivalent;
+
+    fn assert_equivalent(expression: &str, dnf: &str) {
+        let ast = rpn_to_ast(expression).unwrap();
+        let dnf = rpn_to_ast(dnf).unwrap();
+        assert!(equivalent(&ast, &dnf).unwrap(), "{}", expression);
+    }
+
+    #[test]
+    fn test_dnf_conversion() {
+        assert_eq!(disjunctive_normal_form("AB|C&").unwrap(), "AC&BC&|");
+        assert_eq!(disjunctive_normal_form("AB&!").unwrap(), "A!B!|");
+        assert_eq!(disjunctive_normal_form("AB|!").unwrap(), "A!B!&");
+        assert_eq!(
+            disjunctive_normal_form("AB|CD|&").unwrap(),
+            "AC&AD&BC&BD&|||"
+        );
+        assert_eq!(disjunctive_normal_form("AB^").unwrap(), "AB!&A!B&|");
+    }
+
+    #[test]
+    fn test_dnf_preserves_semantics() {
+        for expression in ["AB^C^", "AB=!C|", "AB>C&D=", "AB|C!&D^", "A1&B0|^"] {
+            assert_equivalent(expression, &disjunctive_normal_form(expression).unwrap());
+            assert_equivalent(
+                expression,
+                &disjunctive_normal_form_with(expression, DnfMode::Canonical).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(
+            disjunctive_normal_form_with("AB|", DnfMode::Canonical).unwrap(),
+            "A!B&AB!&AB&||"
+        );
+        assert_eq!(
+            disjunctive_normal_form_with("AB=", DnfMode::Canonical).unwrap(),
+            "A!B!&AB&|"
+        );
+        assert_eq!(
+            disjunctive_normal_form_with("AA!&", DnfMode::Canonical).unwrap(),
+            "0"
+        );
+        assert_eq!(
+            disjunctive_normal_form_with("1", DnfMode::Canonical).unwrap(),
+            "1"
+        );
+    }
+
+    #[test]
+    fn test_terms() {
+        let dnf = Dnf::from_formula(&rpn_to_ast("AB|C!&").unwrap());
+        assert_eq!(
+            dnf.terms,
+            vec![
+                vec![Literal::new(0, false), Literal::new(2, true)],
+                vec![Literal::new(1, false), Literal::new(2, true)],
+            ]
+        );
+        assert!(dnf.evaluate(&[true, false, false]));
+        assert!(!dnf.evaluate(&[true, false, true]));
+
+        let canonical = Dnf::canonical(&rpn_to_ast("AB^").unwrap()).unwrap();
+        assert_eq!(
+            canonical.terms,
+            vec![
+                vec![Literal::new(0, true), Literal::new(1, false)],
+                vec![Literal::new(0, false), Literal::new(1, true)],
+            ]
+        );
+
+        let dnf = Dnf::from_formula(&rpn_to_ast("A0&B1&|").unwrap());
+        assert_eq!(dnf.terms, vec![vec![Literal::new(1, false)]]);
+        assert_eq!(ast_to_string(dnf.to_formula()), "B");
+    }
+
+    #[test]
+    fn test_dnf_invalid() {
+        assert_eq!(
+            disjunctive_normal_form("A|"),
+            Err(ParseError::StackUnderflow {
+                position: 1,
+                operator: '|'
+            })
+        );
+    }
+}
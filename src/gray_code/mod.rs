@@ -1,5 +1,5 @@
 pub fn gray_code(n: u32) -> u32 {
-    return n ^ (n >> 1);
+    n ^ (n >> 1)
 }
 
 #[cfg(test)]
@@ -0,0 +1,447 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::ast::{ast_to_string, rpn_to_ast, Formula, ParseError};
+use crate::gray_code::gray_code;
+use crate::minimize::{minterms_of, Implicant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KarnaughError {
+    Parse(ParseError),
+    UnsupportedVariableCount(usize),
+}
+
+impl fmt::Display for KarnaughError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KarnaughError::Parse(err) => write!(f, "{}", err),
+            KarnaughError::UnsupportedVariableCount(count) => write!(
+                f,
+                "Karnaugh maps need 2 to 6 variables, the formula has {}",
+                count
+            ),
+        }
+    }
+}
+
+impl Error for KarnaughError {}
+
+impl From<ParseError> for KarnaughError {
+    fn from(err: ParseError) -> Self {
+        KarnaughError::Parse(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KarnaughFormat {
+    #[default]
+    Terminal,
+    Markdown,
+    Html,
+}
+
+const HTML_COLORS: [&str; 8] = [
+    "#f4a6a6", "#a6c8f4", "#b5e6a2", "#f4dca6", "#d3b0f0", "#a6ece6", "#f0b0dc", "#d8d8a0",
+];
+
+// A map over 2 to 6 variables. The first variable is the most significant bit
+// of the minterm number; with 5 or 6 variables the leading one or two select
+// one of several 4x4 planes, the rest are split between rows and columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KarnaughMap {
+    pub variables: Vec<String>,
+    values: Vec<bool>,
+}
+
+fn label(names: &[String]) -> String {
+    if names.iter().all(|name| name.chars().count() == 1) {
+        names.concat()
+    } else {
+        names.join(",")
+    }
+}
+
+fn bits(value: usize, width: usize) -> String {
+    (0..width)
+        .rev()
+        .map(|bit| if value >> bit & 1 == 1 { '1' } else { '0' })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn group_name(index: usize) -> String {
+    if index < 26 {
+        ((b'a' + index as u8) as char).to_string()
+    } else {
+        format!("g{}", index)
+    }
+}
+
+impl KarnaughMap {
+    pub fn from_formula(formula: &Formula) -> Result<Self, KarnaughError> {
+        let variables = formula.variables();
+        if !(2..=6).contains(&variables.len()) {
+            return Err(KarnaughError::UnsupportedVariableCount(variables.len()));
+        }
+        let mut values = vec![false; 1 << variables.len()];
+        let minterms = minterms_of(formula).expect("2 to 6 variables fit a truth table");
+        for minterm in minterms {
+            values[minterm as usize] = true;
+        }
+        Ok(KarnaughMap { variables, values })
+    }
+
+    fn plane_vars(&self) -> usize {
+        self.variables.len().saturating_sub(4)
+    }
+
+    fn row_vars(&self) -> usize {
+        (self.variables.len() - self.plane_vars()) / 2
+    }
+
+    fn col_vars(&self) -> usize {
+        self.variables.len() - self.plane_vars() - self.row_vars()
+    }
+
+    // Number of planes, rows and columns.
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        (
+            1 << self.plane_vars(),
+            1 << self.row_vars(),
+            1 << self.col_vars(),
+        )
+    }
+
+    fn gray(index: usize) -> usize {
+        gray_code(index as u32) as usize
+    }
+
+    // The minterm shown at a given position of the map.
+    pub fn cell(&self, plane: usize, row: usize, col: usize) -> usize {
+        let (row_vars, col_vars) = (self.row_vars(), self.col_vars());
+        Self::gray(plane) << (row_vars + col_vars) | Self::gray(row) << col_vars | Self::gray(col)
+    }
+
+    pub fn value(&self, minterm: usize) -> bool {
+        self.values[minterm]
+    }
+
+    fn plane_label(&self, plane: usize) -> Option<String> {
+        let plane_vars = self.plane_vars();
+        if plane_vars == 0 {
+            return None;
+        }
+        Some(format!(
+            "{} = {}",
+            label(&self.variables[..plane_vars]),
+            bits(Self::gray(plane), plane_vars)
+        ))
+    }
+
+    fn corner(&self) -> String {
+        let plane_vars = self.plane_vars();
+        let split = plane_vars + self.row_vars();
+        format!(
+            "{}\\{}",
+            label(&self.variables[plane_vars..split]),
+            label(&self.variables[split..])
+        )
+    }
+
+    fn cell_groups(&self, minterm: usize, groups: &[Implicant]) -> Vec<usize> {
+        (0..groups.len())
+            .filter(|&index| groups[index].covers(minterm as u64))
+            .collect()
+    }
+
+    fn cell_text(&self, minterm: usize, groups: &[Implicant]) -> String {
+        let value = if self.values[minterm] { "1" } else { "0" };
+        let names: String = self
+            .cell_groups(minterm, groups)
+            .into_iter()
+            .map(group_name)
+            .collect();
+        if names.is_empty() {
+            value.to_string()
+        } else {
+            format!("{} {}", value, names)
+        }
+    }
+
+    fn legend(&self, groups: &[Implicant]) -> Vec<(String, String)> {
+        groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| {
+                (
+                    group_name(index),
+                    ast_to_string(group.to_formula(&self.variables)),
+                )
+            })
+            .collect()
+    }
+
+    fn rows_text(&self, plane: usize, groups: &[Implicant]) -> Vec<Vec<String>> {
+        let (_, rows, cols) = self.dimensions();
+        let mut table = vec![std::iter::once(self.corner())
+            .chain((0..cols).map(|col| bits(Self::gray(col), self.col_vars())))
+            .collect::<Vec<String>>()];
+        for row in 0..rows {
+            table.push(
+                std::iter::once(bits(Self::gray(row), self.row_vars()))
+                    .chain((0..cols).map(|col| self.cell_text(self.cell(plane, row, col), groups)))
+                    .collect(),
+            );
+        }
+        table
+    }
+
+    fn render_terminal(&self, groups: &[Implicant]) -> String {
+        let (planes, _, _) = self.dimensions();
+        let mut out = String::new();
+        for plane in 0..planes {
+            if plane > 0 {
+                out.push('\n');
+            }
+            if let Some(label) = self.plane_label(plane) {
+                out.push_str(&format!("{}\n", label));
+            }
+            let table = self.rows_text(plane, groups);
+            let widths: Vec<usize> = (0..table[0].len())
+                .map(|col| {
+                    table
+                        .iter()
+                        .map(|row| row[col].chars().count())
+                        .max()
+                        .unwrap()
+                })
+                .collect();
+            for row in table {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(text, &width)| format!("{:>width$}", text))
+                    .collect();
+                out.push_str(line.join("  ").trim_end());
+                out.push('\n');
+            }
+        }
+        for (name, formula) in self.legend(groups) {
+            out.push_str(&format!("{}: {}\n", name, formula));
+        }
+        out
+    }
+
+    fn render_markdown(&self, groups: &[Implicant]) -> String {
+        let (planes, _, _) = self.dimensions();
+        let mut out = String::new();
+        for plane in 0..planes {
+            if plane > 0 {
+                out.push('\n');
+            }
+            if let Some(label) = self.plane_label(plane) {
+                out.push_str(&format!("**{}**\n\n", label));
+            }
+            let table = self.rows_text(plane, groups);
+            for (index, row) in table.iter().enumerate() {
+                out.push_str(&format!("| {} |\n", row.join(" | ")));
+                if index == 0 {
+                    out.push_str(&format!("|{}\n", "---|".repeat(row.len())));
+                }
+            }
+        }
+        if !groups.is_empty() {
+            out.push('\n');
+            for (name, formula) in self.legend(groups) {
+                out.push_str(&format!("- {}: `{}`\n", name, formula));
+            }
+        }
+        out
+    }
+
+    fn render_html(&self, groups: &[Implicant]) -> String {
+        let (planes, rows, cols) = self.dimensions();
+        let mut out = String::new();
+        for plane in 0..planes {
+            out.push_str("<table class=\"kmap\">\n");
+            if let Some(label) = self.plane_label(plane) {
+                out.push_str(&format!("<caption>{}</caption>\n", escape_html(&label)));
+            }
+            out.push_str(&format!("<tr><th>{}</th>", escape_html(&self.corner())));
+            for col in 0..cols {
+                out.push_str(&format!(
+                    "<th>{}</th>",
+                    bits(Self::gray(col), self.col_vars())
+                ));
+            }
+            out.push_str("</tr>\n");
+            for row in 0..rows {
+                out.push_str(&format!(
+                    "<tr><th>{}</th>",
+                    bits(Self::gray(row), self.row_vars())
+                ));
+                for col in 0..cols {
+                    let minterm = self.cell(plane, row, col);
+                    let value = if self.values[minterm] { 1 } else { 0 };
+                    let cell_groups = self.cell_groups(minterm, groups);
+                    match cell_groups.first() {
+                        None => out.push_str(&format!("<td>{}</td>", value)),
+                        Some(&first) => {
+                            let names: Vec<String> =
+                                cell_groups.into_iter().map(group_name).collect();
+                            out.push_str(&format!(
+                                "<td title=\"{}\" style=\"background: {}\">{}</td>",
+                                names.join(", "),
+                                HTML_COLORS[first % HTML_COLORS.len()],
+                                value
+                            ));
+                        }
+                    }
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+        if !groups.is_empty() {
+            out.push_str("<ul class=\"kmap-groups\">\n");
+            for (index, (name, formula)) in self.legend(groups).into_iter().enumerate() {
+                out.push_str(&format!(
+                    "<li style=\"background: {}\">{}: {}</li>\n",
+                    HTML_COLORS[index % HTML_COLORS.len()],
+                    name,
+                    escape_html(&formula)
+                ));
+            }
+            out.push_str("</ul>\n");
+        }
+        out
+    }
+
+    pub fn render(&self, format: KarnaughFormat, groups: &[Implicant]) -> String {
+        match format {
+            KarnaughFormat::Terminal => self.render_terminal(groups),
+            KarnaughFormat::Markdown => self.render_markdown(groups),
+            KarnaughFormat::Html => self.render_html(groups),
+        }
+    }
+}
+
+pub fn karnaugh_map(expression: &str) -> Result<KarnaughMap, KarnaughError> {
+    KarnaughMap::from_formula(&rpn_to_ast(expression)?)
+}
+
+pub fn print_karnaugh_map(expression: &str) -> Result<(), KarnaughError> {
+    print!(
+        "{}",
+        karnaugh_map(expression)?.render(KarnaughFormat::Terminal, &[])
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minimize::minimize_minterms;
+
+    #[test]
+    fn test_terminal() {
+        let map = karnaugh_map("AB^").unwrap();
+        assert_eq!(
+            map.render(KarnaughFormat::Terminal, &[]),
+            "A\\B  0  1\n  0  0  1\n  1  1  0\n"
+        );
+
+        let map = karnaugh_map("AB&C|").unwrap();
+        assert_eq!(map.dimensions(), (1, 2, 4));
+        assert_eq!(
+            map.render(KarnaughFormat::Terminal, &[]),
+            "A\\BC  00  01  11  10\n   0   0   1   1   0\n   1   0   1   1   1\n"
+        );
+    }
+
+    #[test]
+    fn test_gray_layout() {
+        for expression in ["AB|", "AB|C|", "AB|C|D|", "AB|C|D|E|", "AB|C|D|E|F|"] {
+            let map = karnaugh_map(expression).unwrap();
+            let (planes, rows, cols) = map.dimensions();
+            assert_eq!(planes * rows * cols, 1 << map.variables.len());
+            let mut seen = vec![false; planes * rows * cols];
+            for plane in 0..planes {
+                for row in 0..rows {
+                    for col in 0..cols {
+                        let minterm = map.cell(plane, row, col);
+                        assert!(!seen[minterm]);
+                        seen[minterm] = true;
+                        // Neighbours, wrapping around, differ in exactly one variable.
+                        let right = map.cell(plane, row, (col + 1) % cols);
+                        let below = map.cell(plane, (row + 1) % rows, col);
+                        let next = map.cell((plane + 1) % planes, row, col);
+                        for neighbour in [right, below, next] {
+                            assert!(
+                                neighbour == minterm || (neighbour ^ minterm).count_ones() == 1
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        let map = karnaugh_map("AB|C|D|E|F|").unwrap();
+        assert_eq!(map.dimensions(), (4, 4, 4));
+        assert!(map
+            .render(KarnaughFormat::Terminal, &[])
+            .contains("AB = 11\n"));
+    }
+
+    #[test]
+    fn test_groups() {
+        let map = karnaugh_map("AB&C|").unwrap();
+        let groups = minimize_minterms(3, &[1, 3, 5, 6, 7], &[]);
+        assert_eq!(
+            map.render(KarnaughFormat::Terminal, &groups),
+            "A\\BC  00   01    11   10\n   0   0  1 a   1 a    0\n   1   0  1 a  1 ab  1 b\na: C\nb: AB&\n"
+        );
+        assert_eq!(
+            map.render(KarnaughFormat::Markdown, &groups),
+            "| A\\BC | 00 | 01 | 11 | 10 |\n|---|---|---|---|---|\n\
+             | 0 | 0 | 1 a | 1 a | 0 |\n| 1 | 0 | 1 a | 1 ab | 1 b |\n\n- a: `C`\n- b: `AB&`\n"
+        );
+        let html = map.render(KarnaughFormat::Html, &groups);
+        assert!(html.contains("<th>A\\BC</th><th>00</th><th>01</th><th>11</th><th>10</th>"));
+        assert!(html.contains("<td title=\"a, b\" style=\"background: #f4a6a6\">1</td>"));
+        assert!(html.contains("<li style=\"background: #a6c8f4\">b: AB&amp;</li>"));
+    }
+
+    #[test]
+    fn test_html_escapes_names() {
+        // Names built directly rather than parsed need not be identifiers.
+        let formula = ["a<b", "c&d", "e", "f", "g>h"]
+            .into_iter()
+            .map(Formula::var)
+            .reduce(|left, right| Formula::Or(Box::new(left), Box::new(right)))
+            .unwrap();
+        let html = KarnaughMap::from_formula(&formula)
+            .unwrap()
+            .render(KarnaughFormat::Html, &[]);
+        assert!(html.contains("<caption>a&lt;b = 0</caption>"));
+        assert!(html.contains("<th>c&amp;d,e\\f,g&gt;h</th>"));
+        assert!(!html.contains("a<b") && !html.contains("c&d") && !html.contains("g>h"));
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(
+            karnaugh_map("A"),
+            Err(KarnaughError::UnsupportedVariableCount(1))
+        );
+        assert_eq!(
+            karnaugh_map("ABCDEFG||||||"),
+            Err(KarnaughError::UnsupportedVariableCount(7))
+        );
+        assert!(matches!(karnaugh_map("A|"), Err(KarnaughError::Parse(_))));
+    }
+}
@@ -1,8 +1,17 @@
 pub mod adder;
 pub mod boolean_eval;
 pub mod conjuctive_normal_form;
+pub mod disjunctive_normal_form;
 pub mod gray_code;
 pub mod multiplier;
 pub mod negation_normal_form;
 pub mod truth_table;
 pub mod ast;
+pub mod sat;
+pub mod dimacs;
+pub mod minimize;
+pub mod karnaugh_map;
+pub mod powerset;
+pub mod set_eval;
+pub mod algebra;
+pub mod space_filling_curve;
@@ -1,17 +1,96 @@
+use std::env;
+use std::error::Error;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
 use ready_set_boole::adder::adder;
+use ready_set_boole::ast::{ast_to_string, infix_to_ast, rpn_to_ast};
 use ready_set_boole::boolean_eval::eval_formula;
-use ready_set_boole::conjuctive_normal_form::conjunctive_normal_form;
+use ready_set_boole::conjuctive_normal_form::{
+    conjunctive_normal_form, simplified_conjunctive_normal_form, SimplifyOptions,
+};
+use ready_set_boole::disjunctive_normal_form::disjunctive_normal_form;
 use ready_set_boole::gray_code::gray_code;
+use ready_set_boole::karnaugh_map::print_karnaugh_map;
+use ready_set_boole::minimize::minimize;
 use ready_set_boole::multiplier::multiplier;
 use ready_set_boole::negation_normal_form::negation_normal_form;
-use ready_set_boole::truth_table::print_truth_table;
+use ready_set_boole::powerset::powerset;
+use ready_set_boole::sat::sat;
+use ready_set_boole::set_eval::eval_set;
+use ready_set_boole::space_filling_curve::{map, reverse_map};
+use ready_set_boole::truth_table::{
+    print_truth_table, write_truth_table_with, RowOrder, TruthTableFormat,
+};
 
-fn main() {
+const USAGE: &str =
+    "usage: ready-set-boole [--format terminal|csv|markdown|json|latex|html] [--gray] <formula>";
+
+fn demo() -> Result<(), Box<dyn Error>> {
     println!("3 + 4 = {}", adder(3, 4));
     println!("3 * 4 = {}", multiplier(3, 4));
     println!("Gray code for 3: {}", gray_code(3));
-    println!("01&1| = {}", eval_formula("01&1|"));
-    print_truth_table("AB&C|");
-    println!("AB|! = {}", negation_normal_form("AB|!"));
-    println!("AB|!C!& = {}", conjunctive_normal_form("AB|!C!&"));
+    println!("01&1| = {}", eval_formula("01&1|")?);
+    print_truth_table("AB&C|")?;
+    print_karnaugh_map("AB&C|")?;
+    println!("AB|! = {}", negation_normal_form("AB|!")?);
+    println!("AB|!C!& = {}", conjunctive_normal_form("AB|!C!&")?);
+    println!("AB|C& = {}", disjunctive_normal_form("AB|C&")?);
+    let (simplified, report) =
+        simplified_conjunctive_normal_form("AA|AB|&", SimplifyOptions::default())?;
+    println!(
+        "AA|AB|& = {} ({} clauses removed)",
+        simplified,
+        report.removed_clauses()
+    );
+    println!("AB&A!C&|BC&| minimized = {}", minimize("AB&A!C&|BC&|")?);
+    println!(
+        "A & (B | !C) -> D = {}",
+        ast_to_string(infix_to_ast("A & (B | !C) -> D")?)
+    );
+    println!("AB|C& satisfiable: {}", sat("AB|C&")?);
+    println!("powerset of [1, 2, 3] = {:?}", powerset(&[1, 2, 3])?);
+    println!(
+        "AB& over [0, 1, 2] and [0, 3, 4] = {:?}",
+        eval_set("AB&", &[vec![0, 1, 2], vec![0, 3, 4]])?
+    );
+    let n = map(3, 5);
+    println!("map(3, 5) = {}, reverse_map = {:?}", n, reverse_map(n));
+    Ok(())
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut format = None;
+    let mut order = None;
+    let mut expression = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => format = Some(args.next().ok_or(USAGE)?.parse()?),
+            "-g" | "--gray" => order = Some(RowOrder::Gray),
+            _ if expression.is_none() && !arg.starts_with('-') => expression = Some(arg),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    match (expression, format, order) {
+        (None, None, None) => demo(),
+        (None, _, _) => Err(USAGE.into()),
+        (Some(expression), format, order) => {
+            let format: TruthTableFormat = format.unwrap_or_default();
+            let mut out = BufWriter::new(io::stdout().lock());
+            let formula = rpn_to_ast(&expression)?;
+            write_truth_table_with(&formula, format, order.unwrap_or_default(), &mut out)?;
+            out.flush()?;
+            Ok(())
+        }
+    }
+}
+
+// Errors, including the usage text, go to stderr through `Display`.
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,776 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use crate::ast::{ast_to_string, natural_cmp, rpn_to_ast, Formula, ParseError};
+use crate::conjuctive_normal_form::fold_right;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EspressoError {
+    Parse(ParseError),
+    // Cubes hold inputs and outputs in u64 bitmasks.
+    TooManyInputs(usize),
+    TooManyOutputs(usize),
+}
+
+impl fmt::Display for EspressoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EspressoError::Parse(err) => write!(f, "{}", err),
+            EspressoError::TooManyInputs(count) => {
+                write!(f, "espresso takes at most 64 inputs, got {}", count)
+            }
+            EspressoError::TooManyOutputs(count) => {
+                write!(f, "espresso takes at most 64 outputs, got {}", count)
+            }
+        }
+    }
+}
+
+impl Error for EspressoError {}
+
+impl From<ParseError> for EspressoError {
+    fn from(err: ParseError) -> Self {
+        EspressoError::Parse(err)
+    }
+}
+
+// A product term over at most 64 inputs feeding at most 64 outputs. Bit i of
+// `zero` (`one`) is set if input i may be false (true) inside the cube, so an
+// input with both bits set does not appear in the term. Bit j of `outputs` is
+// set if the term belongs to output j.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cube {
+    pub zero: u64,
+    pub one: u64,
+    pub outputs: u64,
+}
+
+impl Cube {
+    fn universal(full: u64) -> Self {
+        Cube {
+            zero: full,
+            one: full,
+            outputs: 0,
+        }
+    }
+
+    fn literal(full: u64, var: usize, value: bool) -> Self {
+        let bit = 1 << var;
+        if value {
+            Cube {
+                zero: full & !bit,
+                one: full,
+                outputs: 0,
+            }
+        } else {
+            Cube {
+                zero: full,
+                one: full & !bit,
+                outputs: 0,
+            }
+        }
+    }
+
+    fn is_universal(&self, full: u64) -> bool {
+        self.zero & self.one & full == full
+    }
+
+    fn is_empty(&self, full: u64) -> bool {
+        (self.zero | self.one) & full != full
+    }
+
+    fn intersect(&self, other: &Cube) -> Cube {
+        Cube {
+            zero: self.zero & other.zero,
+            one: self.one & other.one,
+            outputs: self.outputs & other.outputs,
+        }
+    }
+
+    fn contains_inputs(&self, other: &Cube) -> bool {
+        other.zero & !self.zero == 0 && other.one & !self.one == 0
+    }
+
+    fn contains(&self, other: &Cube) -> bool {
+        self.contains_inputs(other) && other.outputs & !self.outputs == 0
+    }
+
+    // Inputs that appear in the term, as a positive or a negative literal.
+    fn bound(&self, full: u64) -> u64 {
+        full & !(self.zero & self.one)
+    }
+
+    pub fn literal_count(&self, full: u64) -> usize {
+        self.bound(full).count_ones() as usize
+    }
+}
+
+fn full_mask(num_vars: usize) -> u64 {
+    if num_vars == 64 {
+        u64::MAX
+    } else {
+        (1 << num_vars) - 1
+    }
+}
+
+// Drops every cube whose inputs lie inside another cube of the cover.
+fn single_cube_containment(mut cover: Vec<Cube>, full: u64) -> Vec<Cube> {
+    cover.sort_by_key(|cube| cube.literal_count(full));
+    let mut kept: Vec<Cube> = Vec::new();
+    for cube in cover {
+        if !kept.iter().any(|other| other.contains_inputs(&cube)) {
+            kept.push(cube);
+        }
+    }
+    kept
+}
+
+fn cofactor(cover: &[Cube], cube: &Cube, full: u64) -> Vec<Cube> {
+    cover
+        .iter()
+        .filter(|other| !other.intersect(cube).is_empty(full))
+        .map(|other| Cube {
+            zero: (other.zero | !cube.zero) & full,
+            one: (other.one | !cube.one) & full,
+            outputs: other.outputs,
+        })
+        .collect()
+}
+
+// The variable to split on: the most frequent binate one if there is one,
+// otherwise the most frequent one overall.
+fn splitting_variable(cover: &[Cube], full: u64) -> Option<(usize, bool)> {
+    let mut counts = [[0usize; 2]; 64];
+    for cube in cover {
+        let bound = cube.bound(full);
+        for (var, count) in counts.iter_mut().enumerate() {
+            if bound >> var & 1 == 1 {
+                count[(cube.one >> var & 1) as usize] += 1;
+            }
+        }
+    }
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, [negative, positive])| negative + positive > 0)
+        .max_by(|(a_var, a), (b_var, b)| {
+            let a_binate = a[0] > 0 && a[1] > 0;
+            let b_binate = b[0] > 0 && b[1] > 0;
+            a_binate
+                .cmp(&b_binate)
+                .then((a[0] + a[1]).cmp(&(b[0] + b[1])))
+                .then(b_var.cmp(a_var))
+        })
+        .map(|(var, [negative, positive])| (var, *negative > 0 && *positive > 0))
+}
+
+fn tautology(cover: &[Cube], full: u64) -> bool {
+    if cover.iter().any(|cube| cube.is_universal(full)) {
+        return true;
+    }
+    match splitting_variable(cover, full) {
+        // A unate cover is a tautology only if it contains the universal cube.
+        None | Some((_, false)) => false,
+        Some((var, true)) => [false, true].iter().all(|&value| {
+            let literal = Cube::literal(full, var, value);
+            tautology(&cofactor(cover, &literal, full), full)
+        }),
+    }
+}
+
+fn complement(cover: &[Cube], full: u64) -> Vec<Cube> {
+    if cover.is_empty() {
+        return vec![Cube::universal(full)];
+    }
+    if cover.iter().any(|cube| cube.is_universal(full)) {
+        return Vec::new();
+    }
+    if let [cube] = cover {
+        let bound = cube.bound(full);
+        return (0..64)
+            .filter(|var| bound >> var & 1 == 1)
+            .map(|var| Cube::literal(full, var, cube.one >> var & 1 == 0))
+            .collect();
+    }
+
+    let (var, _) = splitting_variable(cover, full).expect("a non-universal cube binds a variable");
+    let [low, high] = [false, true].map(|value| {
+        let literal = Cube::literal(full, var, value);
+        complement(&cofactor(cover, &literal, full), full)
+    });
+    // Cubes present in both halves do not depend on the splitting variable.
+    let mut result = Vec::new();
+    let mut high_only = high.clone();
+    for cube in low {
+        if let Some(pos) = high_only.iter().position(|other| *other == cube) {
+            high_only.remove(pos);
+            result.push(cube);
+        } else {
+            result.push(cube.intersect(&Cube::literal(full, var, false)));
+        }
+    }
+    result.extend(
+        high_only
+            .into_iter()
+            .map(|cube| cube.intersect(&Cube::literal(full, var, true))),
+    );
+    single_cube_containment(result, full)
+}
+
+fn intersect_covers(a: &[Cube], b: &[Cube], full: u64) -> Vec<Cube> {
+    let mut result = Vec::new();
+    for x in a {
+        for y in b {
+            let cube = x.intersect(y);
+            if !cube.is_empty(full) {
+                result.push(cube);
+            }
+        }
+    }
+    single_cube_containment(result, full)
+}
+
+fn union_covers(mut a: Vec<Cube>, b: Vec<Cube>, full: u64) -> Vec<Cube> {
+    a.extend(b);
+    single_cube_containment(a, full)
+}
+
+// Sum-of-products cover of a formula over the variable order `variables`.
+fn formula_cover(formula: &Formula, variables: &[String], full: u64) -> Vec<Cube> {
+    let cover = |node: &Formula| formula_cover(node, variables, full);
+    match formula {
+        Formula::Var(name) => {
+            let var = variables.iter().position(|v| v == name).unwrap();
+            vec![Cube::literal(full, var, true)]
+        }
+        Formula::Const(true) => vec![Cube::universal(full)],
+        Formula::Const(false) => Vec::new(),
+        Formula::Not(inner) => complement(&cover(inner), full),
+        Formula::And(left, right) => intersect_covers(&cover(left), &cover(right), full),
+        Formula::Or(left, right) => union_covers(cover(left), cover(right), full),
+        Formula::Implies(left, right) => {
+            union_covers(complement(&cover(left), full), cover(right), full)
+        }
+        Formula::Xor(left, right) | Formula::Equiv(left, right) => {
+            let (a, b) = (cover(left), cover(right));
+            let (not_a, not_b) = (complement(&a, full), complement(&b, full));
+            if matches!(formula, Formula::Xor(..)) {
+                union_covers(
+                    intersect_covers(&a, &not_b, full),
+                    intersect_covers(&not_a, &b, full),
+                    full,
+                )
+            } else {
+                union_covers(
+                    intersect_covers(&a, &b, full),
+                    intersect_covers(&not_a, &not_b, full),
+                    full,
+                )
+            }
+        }
+    }
+}
+
+// Joins cubes with identical inputs into one cube feeding all their outputs.
+fn merge_outputs(cubes: Vec<Cube>) -> Vec<Cube> {
+    let mut merged: Vec<Cube> = Vec::new();
+    for cube in cubes {
+        match merged
+            .iter_mut()
+            .find(|other| other.zero == cube.zero && other.one == cube.one)
+        {
+            Some(other) => other.outputs |= cube.outputs,
+            None => merged.push(cube),
+        }
+    }
+    merged
+}
+
+fn with_output(cover: Vec<Cube>, output: usize) -> impl Iterator<Item = Cube> {
+    cover.into_iter().map(move |cube| Cube {
+        outputs: 1 << output,
+        ..cube
+    })
+}
+
+fn for_output(cover: &[Cube], output: usize) -> impl Iterator<Item = Cube> + '_ {
+    cover
+        .iter()
+        .copied()
+        .filter(move |cube| cube.outputs >> output & 1 == 1)
+}
+
+fn outputs_of(cube: &Cube) -> impl Iterator<Item = usize> {
+    let outputs = cube.outputs;
+    (0..64).filter(move |output| outputs >> output & 1 == 1)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub on_set: Formula,
+    pub dont_care: Formula,
+}
+
+impl Function {
+    pub fn new(on_set: Formula) -> Self {
+        Function {
+            on_set,
+            dont_care: Formula::Const(false),
+        }
+    }
+
+    pub fn with_dont_care(on_set: Formula, dont_care: Formula) -> Self {
+        Function { on_set, dont_care }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cover {
+    pub variables: Vec<String>,
+    pub num_outputs: usize,
+    pub cubes: Vec<Cube>,
+}
+
+impl Cover {
+    fn full(&self) -> u64 {
+        full_mask(self.variables.len())
+    }
+
+    // Number of input literals over all cubes, each shared cube counted once.
+    pub fn literal_count(&self) -> usize {
+        let full = self.full();
+        self.cubes.iter().map(|cube| cube.literal_count(full)).sum()
+    }
+
+    pub fn output(&self, output: usize) -> Formula {
+        let full = self.full();
+        let products: Vec<Formula> = for_output(&self.cubes, output)
+            .map(|cube| {
+                let literals: Vec<Formula> = (0..self.variables.len())
+                    .filter(|var| cube.bound(full) >> var & 1 == 1)
+                    .map(|var| {
+                        let name = Formula::var(&self.variables[var]);
+                        if cube.one >> var & 1 == 1 {
+                            name
+                        } else {
+                            Formula::Not(Box::new(name))
+                        }
+                    })
+                    .collect();
+                if literals.is_empty() {
+                    Formula::Const(true)
+                } else {
+                    fold_right(literals, Formula::And)
+                }
+            })
+            .collect();
+        if products.is_empty() {
+            Formula::Const(false)
+        } else {
+            fold_right(products, Formula::Or)
+        }
+    }
+
+    pub fn outputs(&self) -> Vec<Formula> {
+        (0..self.num_outputs)
+            .map(|output| self.output(output))
+            .collect()
+    }
+}
+
+struct Minimizer {
+    full: u64,
+    num_vars: usize,
+    num_outputs: usize,
+    // The on-set and don't-care cubes of the input, never changed.
+    care: Vec<Cube>,
+    dont_care: Vec<Cube>,
+}
+
+impl Minimizer {
+    fn covers(&self, cover: &[Cube], cube: &Cube, output: usize) -> bool {
+        let cover: Vec<Cube> = for_output(cover, output).collect();
+        tautology(&cofactor(&cover, cube, self.full), self.full)
+    }
+
+    // Whether `cube` stays inside the on-set and don't-cares of all its outputs.
+    fn is_implicant(&self, cube: &Cube) -> bool {
+        outputs_of(cube).all(|output| self.covers(&self.care, cube, output))
+    }
+
+    // Whether `cube` is covered for `output` by the other cubes and the don't-cares.
+    fn covered_by_rest(&self, cover: &[Cube], index: usize, cube: &Cube, output: usize) -> bool {
+        let rest: Vec<Cube> = cover
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, cube)| *cube)
+            .chain(self.dont_care.iter().copied())
+            .collect();
+        self.covers(&rest, cube, output)
+    }
+
+    // Grows every cube into a prime, dropping the cubes it swallows.
+    fn expand(&self, mut cover: Vec<Cube>) -> Vec<Cube> {
+        cover.sort_by_key(|cube| cube.literal_count(self.full));
+        let mut expanded: Vec<Cube> = Vec::new();
+        for &cube in &cover {
+            if expanded.iter().any(|other| other.contains(&cube)) {
+                continue;
+            }
+            let mut cube = cube;
+            // First grow towards the nearest cubes that can be swallowed whole,
+            // then raise whatever literals are left.
+            let mut nearest: Vec<&Cube> = cover
+                .iter()
+                .filter(|other| other.outputs & !cube.outputs == 0)
+                .collect();
+            nearest
+                .sort_by_key(|other| (cube.bound(self.full) ^ other.bound(self.full)).count_ones());
+            for other in nearest {
+                let merged = Cube {
+                    zero: cube.zero | other.zero,
+                    one: cube.one | other.one,
+                    ..cube
+                };
+                if merged != cube && self.is_implicant(&merged) {
+                    cube = merged;
+                }
+            }
+            for var in 0..self.num_vars {
+                if cube.bound(self.full) >> var & 1 == 0 {
+                    continue;
+                }
+                let raised = Cube {
+                    zero: cube.zero | 1 << var,
+                    one: cube.one | 1 << var,
+                    ..cube
+                };
+                if self.is_implicant(&raised) {
+                    cube = raised;
+                }
+            }
+            for output in 0..self.num_outputs {
+                if cube.outputs >> output & 1 == 0 && self.covers(&self.care, &cube, output) {
+                    cube.outputs |= 1 << output;
+                }
+            }
+            expanded.retain(|other| !cube.contains(other));
+            expanded.push(cube);
+        }
+        expanded
+    }
+
+    // Removes outputs, and then whole cubes, that the rest of the cover already provides.
+    fn irredundant(&self, mut cover: Vec<Cube>) -> Vec<Cube> {
+        let mut order: Vec<usize> = (0..cover.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(cover[index].literal_count(self.full)));
+        for index in order {
+            for output in outputs_of(&cover[index]) {
+                if self.covered_by_rest(&cover, index, &cover[index], output) {
+                    cover[index].outputs &= !(1 << output);
+                }
+            }
+        }
+        cover.retain(|cube| cube.outputs != 0);
+        cover
+    }
+
+    // Shrinks every cube, one input at a time, as long as the half it gives up
+    // is covered by the rest of the cover.
+    fn reduce(&self, mut cover: Vec<Cube>) -> Vec<Cube> {
+        let mut order: Vec<usize> = (0..cover.len()).collect();
+        order.sort_by_key(|&index| cover[index].literal_count(self.full));
+        for index in order {
+            let mut cube = cover[index];
+            for output in outputs_of(&cover[index]) {
+                if self.covered_by_rest(&cover, index, &cube, output) {
+                    cube.outputs &= !(1 << output);
+                }
+            }
+            if cube.outputs != 0 {
+                for var in 0..self.num_vars {
+                    if cube.bound(self.full) >> var & 1 == 1 {
+                        continue;
+                    }
+                    for value in [false, true] {
+                        let dropped = cube.intersect(&Cube {
+                            outputs: cube.outputs,
+                            ..Cube::literal(self.full, var, value)
+                        });
+                        if outputs_of(&cube)
+                            .all(|output| self.covered_by_rest(&cover, index, &dropped, output))
+                        {
+                            cube = cube.intersect(&Cube {
+                                outputs: cube.outputs,
+                                ..Cube::literal(self.full, var, !value)
+                            });
+                            break;
+                        }
+                    }
+                }
+            }
+            cover[index] = cube;
+        }
+        cover.retain(|cube| cube.outputs != 0);
+        cover
+    }
+
+    fn cost(&self, cover: &[Cube]) -> (usize, usize) {
+        (
+            cover.len(),
+            cover.iter().map(|cube| cube.literal_count(self.full)).sum(),
+        )
+    }
+
+    fn minimize(&self, cover: Vec<Cube>) -> Vec<Cube> {
+        let mut best = self.irredundant(self.expand(cover));
+        loop {
+            let candidate = self.irredundant(self.expand(self.reduce(best.clone())));
+            if self.cost(&candidate).cmp(&self.cost(&best)) != Ordering::Less {
+                return best;
+            }
+            best = candidate;
+        }
+    }
+}
+
+// Heuristic two-level minimization of several functions over shared inputs,
+// in the spirit of Espresso: the cover is expanded into primes, made
+// irredundant, and reduced again for as long as the cost keeps dropping.
+pub fn espresso(functions: &[Function]) -> Result<Cover, EspressoError> {
+    let mut variables: Vec<String> = functions
+        .iter()
+        .flat_map(|f| {
+            f.on_set
+                .variables()
+                .into_iter()
+                .chain(f.dont_care.variables())
+        })
+        .collect();
+    variables.sort_by(|a, b| natural_cmp(a, b));
+    variables.dedup();
+    if variables.len() > 64 {
+        return Err(EspressoError::TooManyInputs(variables.len()));
+    }
+    if functions.len() > 64 {
+        return Err(EspressoError::TooManyOutputs(functions.len()));
+    }
+
+    let full = full_mask(variables.len());
+    let mut on_set = Vec::new();
+    let mut dont_care = Vec::new();
+    for (output, function) in functions.iter().enumerate() {
+        let on = formula_cover(&function.on_set, &variables, full);
+        let dc = formula_cover(&function.dont_care, &variables, full);
+        on_set.extend(with_output(on, output));
+        dont_care.extend(with_output(dc, output));
+    }
+    let care = on_set.iter().chain(&dont_care).copied().collect();
+
+    let minimizer = Minimizer {
+        full,
+        num_vars: variables.len(),
+        num_outputs: functions.len(),
+        care,
+        dont_care,
+    };
+    let mut cubes = minimizer.minimize(merge_outputs(on_set));
+    cubes.sort_by(|a, b| {
+        a.literal_count(full)
+            .cmp(&b.literal_count(full))
+            .then(b.one.cmp(&a.one))
+            .then(a.cmp(b))
+    });
+    Ok(Cover {
+        variables,
+        num_outputs: functions.len(),
+        cubes,
+    })
+}
+
+pub fn espresso_formula(formula: &Formula) -> Result<Formula, EspressoError> {
+    Ok(espresso(&[Function::new(formula.clone())])?.output(0))
+}
+
+pub fn minimize_heuristic(expression: &str) -> Result<String, EspressoError> {
+    let ast = rpn_to_ast(expression)?;
+    Ok(ast_to_string(espresso_formula(&ast)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minimize::{minimize_minterms, minterms_of};
+    use crate::sat::sat;
+
+    fn assert_equivalent(a: &Formula, b: &Formula) {
+        let miter = Formula::Xor(Box::new(a.clone()), Box::new(b.clone()));
+        assert_eq!(sat(&ast_to_string(miter)), Ok(false), "{:?} {:?}", a, b);
+    }
+
+    fn minterm_formula(num_vars: usize, minterms: &[u64]) -> Formula {
+        let names: Vec<String> = (0..num_vars)
+            .map(|var| ((b'A' + var as u8) as char).to_string())
+            .collect();
+        let products = minterms
+            .iter()
+            .map(|&minterm| {
+                let literals = (0..num_vars)
+                    .map(|var| {
+                        let name = Formula::var(&names[var]);
+                        if minterm >> (num_vars - 1 - var) & 1 == 1 {
+                            name
+                        } else {
+                            Formula::Not(Box::new(name))
+                        }
+                    })
+                    .collect();
+                fold_right(literals, Formula::And)
+            })
+            .collect();
+        fold_right(products, Formula::Or)
+    }
+
+    #[test]
+    fn test_tautology_and_complement() {
+        let full = full_mask(2);
+        let a = Cube::literal(full, 0, true);
+        let not_a = Cube::literal(full, 0, false);
+        let b = Cube::literal(full, 1, true);
+        assert!(tautology(&[a, not_a], full));
+        assert!(!tautology(&[a, b], full));
+        assert!(tautology(
+            &[
+                a.intersect(&b),
+                not_a,
+                b.intersect(&not_a),
+                Cube::literal(full, 1, false)
+            ],
+            full
+        ));
+        assert_eq!(
+            complement(&[a, b], full),
+            vec![not_a.intersect(&Cube::literal(full, 1, false))]
+        );
+        assert_eq!(complement(&[], full), vec![Cube::universal(full)]);
+    }
+
+    #[test]
+    fn test_single_output() {
+        assert_eq!(minimize_heuristic("AB&AB!&|"), Ok("A".to_string()));
+        assert_eq!(
+            minimize_heuristic("AB&A!C&|BC&|"),
+            Ok("AB&A!C&|".to_string())
+        );
+        assert_eq!(minimize_heuristic("AA!|"), Ok("1".to_string()));
+        assert_eq!(minimize_heuristic("AA!&"), Ok("0".to_string()));
+        assert_eq!(minimize_heuristic("AB>BC>&"), Ok("BC&A!B!&|".to_string()));
+    }
+
+    #[test]
+    fn test_close_to_exact_on_small_functions() {
+        let (mut total, mut optimal) = (0, 0);
+        for function in (1u64..1 << 16).step_by(97) {
+            let minterms: Vec<u64> = (0..16).filter(|m| function >> m & 1 == 1).collect();
+            let formula = minterm_formula(4, &minterms);
+            let cover = espresso(&[Function::new(formula.clone())]).unwrap();
+            assert_equivalent(&cover.output(0), &formula);
+            let exact = minimize_minterms(4, &minterms, &[]);
+            assert!(cover.cubes.len() <= exact.len() + 1, "{:?}", minterms);
+            total += 1;
+            optimal += (cover.cubes.len() == exact.len()) as usize;
+        }
+        assert!(optimal * 10 >= total * 9, "{} of {}", optimal, total);
+    }
+
+    #[test]
+    fn test_dont_cares() {
+        let on_set = minterm_formula(4, &[4, 8, 10, 11, 12, 15]);
+        let dont_care = minterm_formula(4, &[9, 14]);
+        let cover = espresso(&[Function::with_dont_care(on_set, dont_care)]).unwrap();
+        assert_eq!(cover.cubes.len(), 3);
+        let minterms = minterms_of(&cover.output(0)).unwrap();
+        for m in [4, 8, 10, 11, 12, 15] {
+            assert!(minterms.contains(&m));
+        }
+        for m in [0, 1, 2, 3, 5, 6, 7, 13] {
+            assert!(!minterms.contains(&m));
+        }
+    }
+
+    #[test]
+    fn test_multiple_outputs() {
+        let f = rpn_to_ast("AB&").unwrap();
+        let g = rpn_to_ast("AB&C|").unwrap();
+        let h = rpn_to_ast("ABC&&!A&").unwrap();
+        let cover = espresso(&[
+            Function::new(f.clone()),
+            Function::new(g.clone()),
+            Function::new(h.clone()),
+        ])
+        .unwrap();
+        assert_eq!(cover.variables, ["A", "B", "C"]);
+        // A&B is shared between f and g.
+        assert!(cover.cubes.iter().any(|cube| cube.outputs == 0b011));
+        for (output, formula) in [f, g, h].iter().enumerate() {
+            assert_equivalent(&cover.output(output), formula);
+        }
+        assert_eq!(cover.outputs().len(), 3);
+    }
+
+    #[test]
+    fn test_many_variables() {
+        // Sum of 20 pairs, every other one split on a third variable, plus
+        // terms contained in two neighbouring pairs.
+        let mut expression = String::new();
+        for i in 0..20 {
+            if i % 2 == 0 {
+                expression.push_str(&format!(
+                    "[x_{i}][y_{i}]&[x_{j}]&[x_{i}][y_{i}]&[x_{j}]!&|",
+                    i = i,
+                    j = i + 1
+                ));
+            } else {
+                expression.push_str(&format!("[x_{}][y_{}]&", i, i));
+            }
+            if i > 0 {
+                expression.push('|');
+            }
+        }
+        for i in 0..19 {
+            expression.push_str(&format!("[x_{}][y_{}]&[x_{}]&[y_{}]&|", i, i, i + 1, i + 1));
+        }
+        let formula = rpn_to_ast(&expression).unwrap();
+        let cover = espresso(&[Function::new(formula.clone())]).unwrap();
+        assert_eq!(cover.variables.len(), 40);
+        assert_eq!(cover.cubes.len(), 20);
+        assert_eq!(cover.literal_count(), 40);
+        assert_equivalent(&cover.output(0), &formula);
+    }
+
+    #[test]
+    fn test_limits() {
+        let inputs = |n: usize| {
+            let expression =
+                (0..n).map(|i| format!("[x_{}]", i)).collect::<String>() + &"|".repeat(n - 1);
+            rpn_to_ast(&expression).unwrap()
+        };
+        assert_eq!(
+            espresso_formula(&inputs(65)),
+            Err(EspressoError::TooManyInputs(65))
+        );
+        let cover = espresso(&[Function::new(inputs(64))]).unwrap();
+        assert_eq!(cover.cubes.len(), 64);
+        let functions = vec![Function::new(Formula::var("A")); 65];
+        assert_eq!(
+            espresso(&functions).err(),
+            Some(EspressoError::TooManyOutputs(65))
+        );
+        assert!(matches!(
+            minimize_heuristic("A&"),
+            Err(EspressoError::Parse(_))
+        ));
+    }
+}
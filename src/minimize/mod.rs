@@ -0,0 +1,335 @@
+use std::collections::{BTreeSet, HashSet};
+
+use crate::ast::{ast_to_string, rpn_to_ast, Formula};
+use crate::conjuctive_normal_form::{fold_right, Literal};
+use crate::truth_table::{TruthTable, TruthTableError};
+
+mod espresso;
+
+pub use espresso::{
+    espresso, espresso_formula, minimize_heuristic, Cover, Cube, EspressoError, Function,
+};
+
+// A product term in minterm numbering, where the first variable is the most
+// significant bit. Variables whose bit is set in `mask` do not appear in the
+// term; the others appear with the polarity of their bit in `value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Implicant {
+    pub value: u64,
+    pub mask: u64,
+}
+
+impl Implicant {
+    pub fn minterm(value: u64) -> Self {
+        Implicant { value, mask: 0 }
+    }
+
+    pub fn covers(&self, minterm: u64) -> bool {
+        minterm & !self.mask == self.value
+    }
+
+    // Merges two implicants that differ in exactly one variable.
+    pub fn combine(&self, other: &Implicant) -> Option<Implicant> {
+        let diff = self.value ^ other.value;
+        if self.mask != other.mask || diff.count_ones() != 1 {
+            return None;
+        }
+        Some(Implicant {
+            value: self.value & !diff,
+            mask: self.mask | diff,
+        })
+    }
+
+    pub fn literals(&self, num_vars: usize) -> Vec<Literal> {
+        (0..num_vars)
+            .filter_map(|var| {
+                let bit = 1 << (num_vars - 1 - var);
+                if self.mask & bit != 0 {
+                    None
+                } else {
+                    Some(Literal::new(var, self.value & bit == 0))
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_formula(&self, variables: &[String]) -> Formula {
+        let literals: Vec<Formula> = self
+            .literals(variables.len())
+            .into_iter()
+            .map(|literal| {
+                let var = Formula::var(&variables[literal.var]);
+                if literal.negated {
+                    Formula::Not(Box::new(var))
+                } else {
+                    var
+                }
+            })
+            .collect();
+        if literals.is_empty() {
+            Formula::Const(true)
+        } else {
+            fold_right(literals, Formula::And)
+        }
+    }
+}
+
+pub fn cover_to_formula(cover: &[Implicant], variables: &[String]) -> Formula {
+    if cover.is_empty() {
+        return Formula::Const(false);
+    }
+    let products = cover
+        .iter()
+        .map(|implicant| implicant.to_formula(variables))
+        .collect();
+    fold_right(products, Formula::Or)
+}
+
+fn literal_count(implicant: &Implicant, num_vars: usize) -> usize {
+    num_vars - implicant.mask.count_ones() as usize
+}
+
+pub fn prime_implicants(minterms: &[u64], dont_cares: &[u64]) -> Vec<Implicant> {
+    let mut current: BTreeSet<Implicant> = minterms
+        .iter()
+        .chain(dont_cares)
+        .map(|&value| Implicant::minterm(value))
+        .collect();
+    let mut primes = BTreeSet::new();
+
+    while !current.is_empty() {
+        let terms: Vec<Implicant> = current.iter().copied().collect();
+        let mut combined = HashSet::new();
+        let mut next = BTreeSet::new();
+        for (i, a) in terms.iter().enumerate() {
+            for b in &terms[i + 1..] {
+                if let Some(merged) = a.combine(b) {
+                    combined.insert(*a);
+                    combined.insert(*b);
+                    next.insert(merged);
+                }
+            }
+        }
+        primes.extend(terms.into_iter().filter(|term| !combined.contains(term)));
+        current = next;
+    }
+    primes.into_iter().collect()
+}
+
+// Expands the product of sums over the prime indices covering each minterm
+// into a sum of products, absorbing supersets as it goes.
+fn petrick(primes: &[Implicant], minterms: &[u64]) -> Vec<BTreeSet<usize>> {
+    let mut products: Vec<BTreeSet<usize>> = vec![BTreeSet::new()];
+    for &minterm in minterms {
+        let covering: Vec<usize> = (0..primes.len())
+            .filter(|&i| primes[i].covers(minterm))
+            .collect();
+        let mut expanded: Vec<BTreeSet<usize>> = Vec::new();
+        for product in &products {
+            for &prime in &covering {
+                let mut term = product.clone();
+                term.insert(prime);
+                expanded.push(term);
+            }
+        }
+        expanded.sort_by_key(|term| term.len());
+        let mut absorbed: Vec<BTreeSet<usize>> = Vec::new();
+        for term in expanded {
+            if !absorbed.iter().any(|kept| kept.is_subset(&term)) {
+                absorbed.push(term);
+            }
+        }
+        products = absorbed;
+    }
+    products
+}
+
+// Minimum sum-of-products cover of `minterms`, free to use `dont_cares`.
+// Ties on the number of products are broken by the number of literals.
+pub fn minimize_minterms(num_vars: usize, minterms: &[u64], dont_cares: &[u64]) -> Vec<Implicant> {
+    assert!(num_vars <= 64, "at most 64 variables");
+    assert!(
+        minterms
+            .iter()
+            .chain(dont_cares)
+            .all(|&m| num_vars == 64 || m >> num_vars == 0),
+        "minterm out of range for {} variables",
+        num_vars
+    );
+    let minterms: Vec<u64> = minterms
+        .iter()
+        .copied()
+        .collect::<BTreeSet<u64>>()
+        .into_iter()
+        .collect();
+    let primes: Vec<Implicant> = prime_implicants(&minterms, dont_cares)
+        .into_iter()
+        .filter(|prime| minterms.iter().any(|&m| prime.covers(m)))
+        .collect();
+
+    // Essential primes are the only ones covering some minterm.
+    let mut cover: Vec<Implicant> = Vec::new();
+    for &minterm in &minterms {
+        let covering: Vec<&Implicant> = primes.iter().filter(|p| p.covers(minterm)).collect();
+        if let [essential] = covering.as_slice() {
+            if !cover.contains(essential) {
+                cover.push(**essential);
+            }
+        }
+    }
+    let remaining: Vec<u64> = minterms
+        .iter()
+        .copied()
+        .filter(|&m| !cover.iter().any(|implicant| implicant.covers(m)))
+        .collect();
+    let candidates: Vec<Implicant> = primes
+        .iter()
+        .copied()
+        .filter(|prime| !cover.contains(prime))
+        .collect();
+
+    let best = petrick(&candidates, &remaining)
+        .into_iter()
+        .min_by_key(|term| {
+            let literals: usize = term
+                .iter()
+                .map(|&i| literal_count(&candidates[i], num_vars))
+                .sum();
+            (
+                term.len(),
+                literals,
+                term.iter().copied().collect::<Vec<usize>>(),
+            )
+        })
+        .unwrap_or_default();
+    cover.extend(best.into_iter().map(|i| candidates[i]));
+    cover.sort();
+    cover
+}
+
+pub fn minterms_of(formula: &Formula) -> Result<Vec<u64>, TruthTableError> {
+    Ok(TruthTable::from_formula(formula)?.minterms())
+}
+
+pub fn minimize_formula(formula: &Formula) -> Result<Formula, TruthTableError> {
+    let variables = formula.variables();
+    let cover = minimize_minterms(variables.len(), &minterms_of(formula)?, &[]);
+    Ok(cover_to_formula(&cover, &variables))
+}
+
+pub fn minimize(expression: &str) -> Result<String, TruthTableError> {
+    let ast = rpn_to_ast(expression)?;
+    Ok(ast_to_string(minimize_formula(&ast)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(variables: &str) -> Vec<String> {
+        variables.chars().map(String::from).collect()
+    }
+
+    fn assert_covers(cover: &[Implicant], num_vars: usize, minterms: &[u64], dont_cares: &[u64]) {
+        for m in 0..1u64 << num_vars {
+            if dont_cares.contains(&m) {
+                continue;
+            }
+            assert_eq!(
+                cover.iter().any(|implicant| implicant.covers(m)),
+                minterms.contains(&m),
+                "minterm {} with {:?}",
+                m,
+                cover
+            );
+        }
+    }
+
+    #[test]
+    fn test_combine() {
+        let a = Implicant::minterm(0b0100);
+        let b = Implicant::minterm(0b1100);
+        let merged = a.combine(&b).unwrap();
+        assert_eq!(
+            merged,
+            Implicant {
+                value: 0b0100,
+                mask: 0b1000
+            }
+        );
+        assert!(merged.covers(0b0100) && merged.covers(0b1100));
+        assert!(!merged.covers(0b0101));
+        assert_eq!(a.combine(&Implicant::minterm(0b0111)), None);
+        assert_eq!(merged.combine(&a), None);
+        assert_eq!(
+            merged.literals(4),
+            vec![
+                Literal::new(1, false),
+                Literal::new(2, true),
+                Literal::new(3, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prime_implicants() {
+        // f(A, B, C, D) = m(4, 8, 10, 11, 12, 15) + d(9, 14)
+        let primes = prime_implicants(&[4, 8, 10, 11, 12, 15], &[9, 14]);
+        let formulas: Vec<String> = primes
+            .iter()
+            .map(|p| ast_to_string(p.to_formula(&names("ABCD"))))
+            .collect();
+        assert_eq!(formulas, vec!["BC!D!&&", "AB!&", "AD!&", "AC&"]);
+    }
+
+    #[test]
+    fn test_minimize_with_dont_cares() {
+        let minterms = [4, 8, 10, 11, 12, 15];
+        let dont_cares = [9, 14];
+        let cover = minimize_minterms(4, &minterms, &dont_cares);
+        assert_eq!(cover.len(), 3);
+        assert_covers(&cover, 4, &minterms, &dont_cares);
+        assert_eq!(
+            ast_to_string(cover_to_formula(&cover, &names("ABCD"))),
+            "BC!D!&&AB!&AC&||"
+        );
+    }
+
+    #[test]
+    fn test_cyclic_core() {
+        // Every minterm is covered by exactly two primes, so there is no
+        // essential prime and Petrick's method has to choose.
+        let minterms = [0, 1, 2, 5, 6, 7];
+        assert_eq!(prime_implicants(&minterms, &[]).len(), 6);
+        let cover = minimize_minterms(3, &minterms, &[]);
+        assert_eq!(cover.len(), 3);
+        assert_covers(&cover, 3, &minterms, &[]);
+    }
+
+    #[test]
+    fn test_all_three_variable_functions() {
+        for function in 0u64..256 {
+            let minterms: Vec<u64> = (0..8).filter(|m| function >> m & 1 == 1).collect();
+            let cover = minimize_minterms(3, &minterms, &[]);
+            assert_covers(&cover, 3, &minterms, &[]);
+            // Irredundant: dropping any product loses a minterm.
+            for skip in 0..cover.len() {
+                assert!(minterms.iter().any(|&m| !cover
+                    .iter()
+                    .enumerate()
+                    .any(|(i, implicant)| i != skip && implicant.covers(m))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize_expression() {
+        assert_eq!(minimize("AB&AB!&|"), Ok("A".to_string()));
+        assert_eq!(minimize("AB&A!C&|BC&|"), Ok("A!C&AB&|".to_string()));
+        assert_eq!(minimize("AA!|"), Ok("1".to_string()));
+        assert_eq!(minimize("AA!&"), Ok("0".to_string()));
+        assert_eq!(minimize("AB^"), Ok("A!B&AB!&|".to_string()));
+        assert_eq!(minimize("[x_1][x_2]|[x_1]&"), Ok("[x_1]".to_string()));
+        assert!(minimize("A&").is_err());
+    }
+}
@@ -12,7 +12,7 @@ pub fn multiplier(a: u32, b: u32) -> u32 {
         };
         rec(a << 1, b >> 1, reg)
     }
-    return rec(a, b, 0);
+    rec(a, b, 0)
 }
 
 #[cfg(test)]
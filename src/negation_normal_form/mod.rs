@@ -1,12 +1,9 @@
-use crate::ast::{ast_to_string, rpn_to_ast, to_nnf};
+use crate::ast::{ast_to_string, rpn_to_ast, to_nnf, ParseError};
 
-pub fn negation_normal_form(expression: &str) -> String {
-    if let Some(ast) = rpn_to_ast(expression) {
-        let nnf_ast = to_nnf(ast);
-        ast_to_string(nnf_ast)
-    } else {
-        return "Invalid expression".to_string();
-    }
+pub fn negation_normal_form(expression: &str) -> Result<String, ParseError> {
+    let ast = rpn_to_ast(expression)?;
+    let nnf_ast = to_nnf(ast);
+    Ok(ast_to_string(nnf_ast))
 }
 
 #[cfg(test)]
@@ -15,17 +12,44 @@ mod tests {
 
     #[test]
     fn test_negation_normal_form() {
-        assert_eq!(negation_normal_form("AB&!"), "A!B!|");
-        assert_eq!(negation_normal_form("AB|!"), "A!B!&");
-        assert_eq!(negation_normal_form("AB>"), "A!B|");
-        assert_eq!(negation_normal_form("AB="), "AB&A!B!&|");
-        assert_eq!(negation_normal_form("AB|C&!"), "A!B!&C!|");
+        assert_eq!(negation_normal_form("AB&!").unwrap(), "A!B!|");
+        assert_eq!(negation_normal_form("AB|!").unwrap(), "A!B!&");
+        assert_eq!(negation_normal_form("AB>").unwrap(), "A!B|");
+        assert_eq!(negation_normal_form("AB=").unwrap(), "AB&A!B!&|");
+        assert_eq!(negation_normal_form("AB|C&!").unwrap(), "A!B!&C!|");
+    }
+
+    #[test]
+    fn test_negated_operators() {
+        assert_eq!(negation_normal_form("AB>!").unwrap(), "AB!&");
+        assert_eq!(negation_normal_form("AB=!").unwrap(), "AB!&A!B&|");
+        assert_eq!(negation_normal_form("1!").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_xor() {
+        assert_eq!(negation_normal_form("AB^").unwrap(), "AB!&A!B&|");
+        assert_eq!(negation_normal_form("AB^!").unwrap(), "AB&A!B!&|");
+        assert_eq!(negation_normal_form("AB^C&!").unwrap(), "AB&A!B!&|C!|");
     }
 
     #[test]
     fn test_invalid_expression() {
-        assert_eq!(negation_normal_form("ABBB"), "Invalid expression");
-        assert_eq!(negation_normal_form(""), "Invalid expression");
-        assert_eq!(negation_normal_form("!AB"), "Invalid expression");
+        assert_eq!(
+            negation_normal_form("ABBB"),
+            Err(ParseError::LeftoverOperands {
+                position: 1,
+                symbol: 'B',
+                count: 4
+            })
+        );
+        assert_eq!(negation_normal_form(""), Err(ParseError::EmptyInput));
+        assert_eq!(
+            negation_normal_form("!AB"),
+            Err(ParseError::StackUnderflow {
+                position: 0,
+                operator: '!'
+            })
+        );
     }
 }
@@ -0,0 +1,199 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubsetOrder {
+    // Subset k holds the elements at the set bits of k.
+    #[default]
+    Binary,
+    // Successive subsets differ by a single element.
+    Gray,
+}
+
+// Subset masks are u64, which leaves room for 63 elements.
+pub const MAX_ELEMENTS: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManyElements(pub usize);
+
+impl fmt::Display for TooManyElements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "subsets are limited to sets of {} elements, got {}",
+            MAX_ELEMENTS, self.0
+        )
+    }
+}
+
+impl Error for TooManyElements {}
+
+// Lazily enumerated subsets of a slice, element i being bit i of the subset
+// mask. Only the subset being yielded is allocated.
+#[derive(Debug, Clone)]
+pub struct Subsets<T> {
+    elements: Vec<T>,
+    order: SubsetOrder,
+    next: u64,
+    end: u64,
+}
+
+impl<T: Clone> Subsets<T> {
+    pub fn new(elements: &[T]) -> Result<Self, TooManyElements> {
+        Subsets::with_order(elements, SubsetOrder::Binary)
+    }
+
+    pub fn with_order(elements: &[T], order: SubsetOrder) -> Result<Self, TooManyElements> {
+        if elements.len() > MAX_ELEMENTS {
+            return Err(TooManyElements(elements.len()));
+        }
+        Ok(Subsets {
+            elements: elements.to_vec(),
+            order,
+            next: 0,
+            end: 1 << elements.len(),
+        })
+    }
+
+    pub fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    // The elements at the set bits of `mask`, in their original order.
+    pub fn subset(&self, mask: u64) -> Vec<T> {
+        self.elements
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| mask >> i & 1 == 1)
+            .map(|(_, element)| element.clone())
+            .collect()
+    }
+}
+
+impl<T: Clone> Iterator for Subsets<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        let index = self.next;
+        self.next += 1;
+        let mask = match self.order {
+            SubsetOrder::Binary => index,
+            SubsetOrder::Gray => index ^ index >> 1,
+        };
+        Some(self.subset(mask))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.end - self.next).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+pub fn subsets<T: Clone>(set: &[T]) -> Result<Subsets<T>, TooManyElements> {
+    Subsets::new(set)
+}
+
+pub fn subsets_with<T: Clone>(
+    set: &[T],
+    order: SubsetOrder,
+) -> Result<Subsets<T>, TooManyElements> {
+    Subsets::with_order(set, order)
+}
+
+pub fn powerset(set: &[i32]) -> Result<Vec<Vec<i32>>, TooManyElements> {
+    Ok(subsets(set)?.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_powerset() {
+        assert_eq!(powerset(&[]).unwrap(), vec![Vec::<i32>::new()]);
+        assert_eq!(
+            powerset(&[1, 2, 3]).unwrap(),
+            vec![
+                vec![],
+                vec![1],
+                vec![2],
+                vec![1, 2],
+                vec![3],
+                vec![1, 3],
+                vec![2, 3],
+                vec![1, 2, 3],
+            ]
+        );
+
+        let mut sets = powerset(&[4, -1, 7, 0, 12]).unwrap();
+        assert_eq!(sets.len(), 32);
+        sets.sort();
+        sets.dedup();
+        assert_eq!(sets.len(), 32);
+    }
+
+    #[test]
+    fn test_gray_order() {
+        let sets: Vec<Vec<char>> = subsets_with(&['a', 'b', 'c'], SubsetOrder::Gray)
+            .unwrap()
+            .collect();
+        assert_eq!(
+            sets,
+            vec![
+                vec![],
+                vec!['a'],
+                vec!['a', 'b'],
+                vec!['b'],
+                vec!['b', 'c'],
+                vec!['a', 'b', 'c'],
+                vec!['a', 'c'],
+                vec!['c'],
+            ]
+        );
+        for pair in sets.windows(2) {
+            let changed = ['a', 'b', 'c']
+                .iter()
+                .filter(|c| pair[0].contains(c) != pair[1].contains(c))
+                .count();
+            assert_eq!(changed, 1);
+        }
+    }
+
+    #[test]
+    fn test_lazy() {
+        let names = vec!["x".to_string(); 40];
+        let mut subsets = subsets(&names).unwrap();
+        assert_eq!(subsets.size_hint(), (1 << 40, Some(1 << 40)));
+        assert_eq!(subsets.nth(5), Some(vec!["x".to_string(); 2]));
+        assert_eq!(subsets.size_hint().1, Some((1 << 40) - 6));
+
+        let found = Subsets::new(&[3, 9, 14, 20, 25])
+            .unwrap()
+            .find(|subset| subset.iter().sum::<i32>() == 37)
+            .unwrap();
+        assert_eq!(found, [3, 14, 20]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let elements: Vec<u32> = (0..64).collect();
+        assert_eq!(powerset(&[0; 64]), Err(TooManyElements(64)));
+        for (order, last) in [
+            (SubsetOrder::Binary, elements[..63].to_vec()),
+            (SubsetOrder::Gray, vec![62]),
+        ] {
+            assert_eq!(
+                subsets_with(&elements, order).err(),
+                Some(TooManyElements(64))
+            );
+            let mut subsets = subsets_with(&elements[..63], order).unwrap();
+            assert_eq!(subsets.size_hint().1, Some(1 << 63));
+            subsets.next = (1 << 63) - 1;
+            assert_eq!(subsets.next(), Some(last));
+            assert_eq!(subsets.next(), None);
+        }
+    }
+}
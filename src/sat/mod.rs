@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::ast::{rpn_to_ast, ParseError};
+use crate::conjuctive_normal_form::{to_cnf_tseitin, Cnf};
+
+mod solver;
+
+pub use solver::{Solver, SolverStats};
+
+pub fn solve_cnf(cnf: &Cnf) -> Option<Vec<bool>> {
+    Solver::from_cnf(cnf).solve()
+}
+
+pub fn solve(expression: &str) -> Result<Option<HashMap<String, bool>>, ParseError> {
+    let tseitin = to_cnf_tseitin(&rpn_to_ast(expression)?);
+    let names = &tseitin.cnf.variables.names()[..tseitin.original_vars];
+    Ok(solve_cnf(&tseitin.cnf).map(|model| names.iter().cloned().zip(model).collect()))
+}
+
+pub fn sat(expression: &str) -> Result<bool, ParseError> {
+    Ok(solve(expression)?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::truth_table::count_models;
+
+    #[test]
+    fn test_sat() {
+        assert_eq!(sat("AB|"), Ok(true));
+        assert_eq!(sat("AB&"), Ok(true));
+        assert_eq!(sat("AA!&"), Ok(false));
+        assert_eq!(sat("AA^"), Ok(false));
+        assert_eq!(sat("AA!|"), Ok(true));
+        assert_eq!(sat("1"), Ok(true));
+        assert_eq!(sat("0"), Ok(false));
+        assert_eq!(sat("AB>A&B!&"), Ok(false));
+        assert_eq!(sat("[x_1][x_2]^[x_1]&"), Ok(true));
+    }
+
+    #[test]
+    fn test_solve_model() {
+        for expression in [
+            "AB&",
+            "AB^C&",
+            "AB>A&",
+            "AB|C!&D=",
+            "[req][ack]>[req]&",
+            "ABC&&!A&B&",
+        ] {
+            let model = solve(expression).unwrap().unwrap();
+            let ast = rpn_to_ast(expression).unwrap();
+            assert_eq!(model.len(), ast.variables().len(), "{}", expression);
+            assert!(ast.evaluate(&model), "{} with {:?}", expression, model);
+        }
+        let model = solve("AB!&").unwrap().unwrap();
+        assert!(model["A"]);
+        assert!(!model["B"]);
+        assert_eq!(solve("AB=A!B&&"), Ok(None));
+    }
+
+    #[test]
+    fn test_sat_matches_brute_force() {
+        let expressions = [
+            "AB^BC^&CA^&",
+            "AB^BC^&AC^&",
+            "AB|A!B|&AB!|&A!B!|&",
+            "AB>BC>&CA>&A!&",
+            "AB=C^D|A!&",
+            "ABCD&&&!A&B&C&",
+        ];
+        for expression in expressions {
+            let ast = rpn_to_ast(expression).unwrap();
+            assert_eq!(
+                sat(expression),
+                Ok(count_models(&ast).unwrap() > 0),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn test_many_variables() {
+        let chain: String = (0..60)
+            .map(|i| format!("[x_{}][x_{}]=", i, i + 1))
+            .collect();
+        let mut expression = chain.clone() + &"&".repeat(59);
+        assert_eq!(sat(&expression), Ok(true));
+        expression.push_str("[x_0][x_60]!&&");
+        assert_eq!(sat(&expression), Ok(false));
+
+        let model = solve(&(chain + &"&".repeat(59) + "[x_0]&"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.len(), 61);
+        assert!(model.values().all(|&value| value));
+    }
+
+    #[test]
+    fn test_no_cnf_blowup() {
+        let mut expression = String::new();
+        for i in 0..40 {
+            expression.push_str(&format!("[a_{}][b_{}]&", i, i));
+            if i > 0 {
+                expression.push('|');
+            }
+        }
+        let model = solve(&(expression + "[a_0]![a_39]!&&")).unwrap().unwrap();
+        assert_eq!(model.len(), 80);
+        assert!((1..39).any(|i| model[&format!("a_{}", i)] && model[&format!("b_{}", i)]));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            sat("A&"),
+            Err(ParseError::StackUnderflow {
+                position: 1,
+                operator: '&'
+            })
+        );
+    }
+}
@@ -0,0 +1,491 @@
+use std::collections::BinaryHeap;
+
+use crate::conjuctive_normal_form::{Cnf, Literal};
+
+const ACTIVITY_DECAY: f64 = 0.95;
+const ACTIVITY_LIMIT: f64 = 1e100;
+const RESTART_BASE: u64 = 100;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub learnt_clauses: u64,
+    pub restarts: u64,
+}
+
+// Watch lists are indexed by literal: `2 * var` when positive, `2 * var + 1` when negated.
+fn index(literal: Literal) -> usize {
+    literal.var * 2 + literal.negated as usize
+}
+
+// Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ... used to space restarts.
+fn luby(mut i: u64) -> u64 {
+    let mut size = 1;
+    let mut seq = 0;
+    while size < i + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != i {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        i %= size;
+    }
+    1 << seq
+}
+
+#[derive(Debug, Clone)]
+pub struct Solver {
+    clauses: Vec<Vec<Literal>>,
+    watches: Vec<Vec<usize>>,
+    assignment: Vec<Option<bool>>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    trail: Vec<Literal>,
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    activity: Vec<f64>,
+    activity_increment: f64,
+    order: BinaryHeap<(u64, usize)>,
+    phase: Vec<bool>,
+    seen: Vec<bool>,
+    inconsistent: bool,
+    stats: SolverStats,
+}
+
+impl Solver {
+    pub fn new(num_vars: usize) -> Self {
+        Solver {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); num_vars * 2],
+            assignment: vec![None; num_vars],
+            level: vec![0; num_vars],
+            reason: vec![None; num_vars],
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            propagated: 0,
+            activity: vec![0.0; num_vars],
+            activity_increment: 1.0,
+            order: (0..num_vars).map(|var| (0, var)).collect(),
+            phase: vec![false; num_vars],
+            seen: vec![false; num_vars],
+            inconsistent: false,
+            stats: SolverStats::default(),
+        }
+    }
+
+    pub fn from_cnf(cnf: &Cnf) -> Self {
+        let mut solver = Solver::new(cnf.variables.len());
+        for clause in &cnf.clauses {
+            solver.add_clause(clause);
+        }
+        solver
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.assignment.len()
+    }
+
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    // Returns false once the clause database is known to be unsatisfiable.
+    pub fn add_clause(&mut self, clause: &[Literal]) -> bool {
+        if self.inconsistent {
+            return false;
+        }
+        self.cancel_until(0);
+
+        let mut literals: Vec<Literal> = clause.to_vec();
+        literals.sort();
+        literals.dedup();
+        if literals.windows(2).any(|pair| pair[0].var == pair[1].var) {
+            return true;
+        }
+        if literals.iter().any(|&l| self.value(l) == Some(true)) {
+            return true;
+        }
+        literals.retain(|&l| self.value(l).is_none());
+
+        match literals.len() {
+            0 => self.inconsistent = true,
+            1 => {
+                self.assign(literals[0], None);
+                if self.propagate().is_some() {
+                    self.inconsistent = true;
+                }
+            }
+            _ => {
+                self.attach(literals);
+            }
+        }
+        !self.inconsistent
+    }
+
+    pub fn solve(&mut self) -> Option<Vec<bool>> {
+        if self.inconsistent {
+            return None;
+        }
+        let mut restart_count = 0;
+        let mut conflict_budget = luby(restart_count) * RESTART_BASE;
+
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.inconsistent = true;
+                    return None;
+                }
+                let (learnt, backjump_level) = self.analyze(conflict);
+                self.cancel_until(backjump_level);
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let asserting = learnt[0];
+                    let clause = self.attach(learnt);
+                    self.assign(asserting, Some(clause));
+                }
+                self.stats.learnt_clauses += 1;
+                self.activity_increment /= ACTIVITY_DECAY;
+
+                conflict_budget -= 1;
+                if conflict_budget == 0 {
+                    self.stats.restarts += 1;
+                    restart_count += 1;
+                    conflict_budget = luby(restart_count) * RESTART_BASE;
+                    self.cancel_until(0);
+                }
+            } else {
+                let Some(var) = self.pick_branch_var() else {
+                    let model = self
+                        .assignment
+                        .iter()
+                        .map(|value| value.unwrap_or(false))
+                        .collect();
+                    self.cancel_until(0);
+                    return Some(model);
+                };
+                self.stats.decisions += 1;
+                self.trail_limits.push(self.trail.len());
+                self.assign(Literal::new(var, !self.phase[var]), None);
+            }
+        }
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn value(&self, literal: Literal) -> Option<bool> {
+        self.assignment[literal.var].map(|value| literal.is_satisfied_by(value))
+    }
+
+    fn attach(&mut self, literals: Vec<Literal>) -> usize {
+        let clause = self.clauses.len();
+        self.watches[index(literals[0])].push(clause);
+        self.watches[index(literals[1])].push(clause);
+        self.clauses.push(literals);
+        clause
+    }
+
+    fn assign(&mut self, literal: Literal, reason: Option<usize>) {
+        self.assignment[literal.var] = Some(!literal.negated);
+        self.level[literal.var] = self.decision_level();
+        self.reason[literal.var] = reason;
+        self.trail.push(literal);
+    }
+
+    // Returns the index of a conflicting clause, if any.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = self.trail[self.propagated].negate();
+            self.propagated += 1;
+            self.stats.propagations += 1;
+
+            let watching = std::mem::take(&mut self.watches[index(falsified)]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+
+            for (position, &clause_index) in watching.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watching[position..]);
+                    break;
+                }
+                let clause = &mut self.clauses[clause_index];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.assignment[first.var].map(|v| first.is_satisfied_by(v)) == Some(true) {
+                    kept.push(clause_index);
+                    continue;
+                }
+
+                let replacement = (2..clause.len()).find(|&k| {
+                    let l = clause[k];
+                    self.assignment[l.var].map(|v| l.is_satisfied_by(v)) != Some(false)
+                });
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    let watch = index(clause[1]);
+                    self.watches[watch].push(clause_index);
+                    continue;
+                }
+
+                kept.push(clause_index);
+                match self.value(first) {
+                    Some(false) => conflict = Some(clause_index),
+                    _ => self.assign(first, Some(clause_index)),
+                }
+            }
+            self.watches[index(falsified)] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // First-UIP conflict analysis; returns the learnt clause, asserting literal first,
+    // and the level to backjump to.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Literal>, usize) {
+        let mut learnt = vec![Literal::new(0, false)];
+        let mut pending = 0;
+        let mut clause_index = conflict;
+        let mut trail_index = self.trail.len();
+        let mut implied: Option<Literal> = None;
+
+        loop {
+            let start = if implied.is_some() { 1 } else { 0 };
+            for k in start..self.clauses[clause_index].len() {
+                let literal = self.clauses[clause_index][k];
+                let var = literal.var;
+                if self.seen[var] || self.level[var] == 0 {
+                    continue;
+                }
+                self.seen[var] = true;
+                self.bump(var);
+                if self.level[var] == self.decision_level() {
+                    pending += 1;
+                } else {
+                    learnt.push(literal);
+                }
+            }
+
+            loop {
+                trail_index -= 1;
+                if self.seen[self.trail[trail_index].var] {
+                    break;
+                }
+            }
+            let literal = self.trail[trail_index];
+            self.seen[literal.var] = false;
+            implied = Some(literal);
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            clause_index = self.reason[literal.var].expect("implied literal has a reason");
+        }
+        learnt[0] = implied.unwrap().negate();
+
+        for literal in &learnt[1..] {
+            self.seen[literal.var] = false;
+        }
+
+        let mut backjump_level = 0;
+        if learnt.len() > 1 {
+            let (position, _) = learnt
+                .iter()
+                .enumerate()
+                .skip(1)
+                .max_by_key(|(_, l)| self.level[l.var])
+                .unwrap();
+            learnt.swap(1, position);
+            backjump_level = self.level[learnt[1].var];
+        }
+        (learnt, backjump_level)
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.activity_increment;
+        if self.activity[var] > ACTIVITY_LIMIT {
+            for activity in &mut self.activity {
+                *activity /= ACTIVITY_LIMIT;
+            }
+            self.activity_increment /= ACTIVITY_LIMIT;
+            self.order = (0..self.num_vars())
+                .filter(|&v| self.assignment[v].is_none())
+                .map(|v| (self.activity[v].to_bits(), v))
+                .collect();
+        }
+        self.order.push((self.activity[var].to_bits(), var));
+    }
+
+    // The heap may hold stale entries; only an unassigned variable whose entry
+    // matches its current activity is a valid pick.
+    fn pick_branch_var(&mut self) -> Option<usize> {
+        while let Some((activity, var)) = self.order.pop() {
+            if self.assignment[var].is_none() && activity == self.activity[var].to_bits() {
+                return Some(var);
+            }
+        }
+        (0..self.num_vars()).find(|&var| self.assignment[var].is_none())
+    }
+
+    fn cancel_until(&mut self, level: usize) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for literal in self.trail.drain(limit..).rev() {
+            self.phase[literal.var] = !literal.negated;
+            self.assignment[literal.var] = None;
+            self.reason[literal.var] = None;
+            self.order
+                .push((self.activity[literal.var].to_bits(), literal.var));
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.trail.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conjuctive_normal_form::cnf;
+
+    fn brute_force(cnf: &Cnf) -> bool {
+        let n = cnf.variables.len();
+        (0..1u32 << n).any(|mask| {
+            let assignment: Vec<bool> = (0..n).map(|i| mask >> i & 1 == 1).collect();
+            cnf.evaluate(&assignment)
+        })
+    }
+
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    fn random_3sat(rng: &mut XorShift, num_vars: usize, num_clauses: usize) -> Cnf {
+        let clauses: Vec<Vec<i32>> = (0..num_clauses)
+            .map(|_| {
+                (0..3)
+                    .map(|_| {
+                        let var = (rng.next() % num_vars as u64) as i32 + 1;
+                        if rng.next() & 1 == 1 {
+                            -var
+                        } else {
+                            var
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        cnf(num_vars, &clauses)
+    }
+
+    fn pigeonhole(pigeons: usize, holes: usize) -> Cnf {
+        let var = |p: usize, h: usize| (p * holes + h + 1) as i32;
+        let mut clauses = Vec::new();
+        for p in 0..pigeons {
+            clauses.push((0..holes).map(|h| var(p, h)).collect());
+        }
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![-var(p, h), -var(q, h)]);
+                }
+            }
+        }
+        cnf(pigeons * holes, &clauses)
+    }
+
+    #[test]
+    fn test_luby() {
+        let sequence: Vec<u64> = (0..15).map(luby).collect();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_trivial() {
+        assert_eq!(Solver::from_cnf(&cnf(0, &[])).solve(), Some(vec![]));
+        assert_eq!(Solver::from_cnf(&cnf(1, &[vec![]])).solve(), None);
+        assert_eq!(
+            Solver::from_cnf(&cnf(1, &[vec![1], vec![-1]])).solve(),
+            None
+        );
+        assert_eq!(
+            Solver::from_cnf(&cnf(2, &[vec![-1], vec![1, 2]])).solve(),
+            Some(vec![false, true])
+        );
+        assert_eq!(
+            Solver::from_cnf(&cnf(1, &[vec![1, -1]]))
+                .solve()
+                .map(|m| m.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_random_matches_brute_force() {
+        let mut rng = XorShift(0x9E3779B97F4A7C15);
+        for _ in 0..200 {
+            let instance = random_3sat(&mut rng, 12, 52);
+            let mut solver = Solver::from_cnf(&instance);
+            match solver.solve() {
+                Some(model) => assert!(instance.evaluate(&model)),
+                None => assert!(!brute_force(&instance)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_random() {
+        let mut rng = XorShift(42);
+        for _ in 0..20 {
+            let instance = random_3sat(&mut rng, 60, 240);
+            if let Some(model) = Solver::from_cnf(&instance).solve() {
+                assert!(instance.evaluate(&model));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pigeonhole() {
+        let mut solver = Solver::from_cnf(&pigeonhole(6, 5));
+        assert_eq!(solver.solve(), None);
+        assert!(solver.stats().learnt_clauses > 0);
+
+        let instance = pigeonhole(5, 5);
+        let model = Solver::from_cnf(&instance).solve().unwrap();
+        assert!(instance.evaluate(&model));
+    }
+
+    #[test]
+    fn test_incremental() {
+        let instance = cnf(3, &[vec![1, 2, 3]]);
+        let mut solver = Solver::from_cnf(&instance);
+        let mut models = 0;
+        while let Some(model) = solver.solve() {
+            models += 1;
+            let blocking: Vec<Literal> = model
+                .iter()
+                .enumerate()
+                .map(|(var, &value)| Literal::new(var, value))
+                .collect();
+            solver.add_clause(&blocking);
+        }
+        assert_eq!(models, 7);
+    }
+}
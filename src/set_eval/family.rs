@@ -0,0 +1,231 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::ast::{ast_to_string, is_valid_name, rpn_to_ast, Formula, VarTable};
+use crate::conjuctive_normal_form::Literal;
+use crate::disjunctive_normal_form::Dnf;
+use crate::truth_table::{Rows, TruthTableError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FamilyError {
+    InvalidName(String),
+    DuplicateVariable(String),
+    // A member of the family holds an element missing from the variables.
+    UnknownElement(String),
+}
+
+impl fmt::Display for FamilyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FamilyError::InvalidName(name) => write!(f, "'{}' is not a valid variable name", name),
+            FamilyError::DuplicateVariable(name) => {
+                write!(f, "variable '{}' is listed more than once", name)
+            }
+            FamilyError::UnknownElement(name) => {
+                write!(f, "element '{}' is not one of the variables", name)
+            }
+        }
+    }
+}
+
+impl Error for FamilyError {}
+
+// Every model of the formula as the set of its true variables. Models follow
+// truth table order and list their variables in the natural sort order of
+// `Formula::variables`, so `x_2` comes before `x_10`.
+pub fn models(formula: &Formula) -> Result<Vec<Vec<String>>, TruthTableError> {
+    let rows = Rows::new(formula)?;
+    let models = rows
+        .clone()
+        .filter(|&(_, output)| output)
+        .map(|(mask, _)| {
+            rows.variables()
+                .iter()
+                .enumerate()
+                .filter(|&(var, _)| rows.input(mask, var))
+                .map(|(_, name)| name.clone())
+                .collect()
+        })
+        .collect();
+    Ok(models)
+}
+
+pub fn formula_models(expression: &str) -> Result<Vec<Vec<String>>, TruthTableError> {
+    models(&rpn_to_ast(expression)?)
+}
+
+// The formula true exactly on the members of the family, as a canonical DNF
+// over `variables`: each member is the assignment setting its elements true
+// and every other variable false.
+pub fn family_to_formula<S: AsRef<str>>(
+    variables: &[S],
+    family: &[Vec<S>],
+) -> Result<Formula, FamilyError> {
+    let mut table = VarTable::new();
+    for name in variables.iter().map(AsRef::as_ref) {
+        if !is_valid_name(name) {
+            return Err(FamilyError::InvalidName(name.to_string()));
+        }
+        if table.index_of(name).is_some() {
+            return Err(FamilyError::DuplicateVariable(name.to_string()));
+        }
+        table.insert(name);
+    }
+
+    let mut members = Vec::with_capacity(family.len());
+    for set in family {
+        let mut member = vec![false; table.len()];
+        for name in set.iter().map(AsRef::as_ref) {
+            let var = table
+                .index_of(name)
+                .ok_or_else(|| FamilyError::UnknownElement(name.to_string()))?;
+            member[var] = true;
+        }
+        members.push(member);
+    }
+    members.sort();
+    members.dedup();
+
+    let terms = members
+        .iter()
+        .map(|member| {
+            member
+                .iter()
+                .enumerate()
+                .map(|(var, &value)| Literal::new(var, !value))
+                .collect()
+        })
+        .collect();
+    Ok(Dnf {
+        variables: table,
+        terms,
+    }
+    .to_formula())
+}
+
+pub fn family_formula<S: AsRef<str>>(
+    variables: &[S],
+    family: &[Vec<S>],
+) -> Result<String, FamilyError> {
+    Ok(ast_to_string(family_to_formula(variables, family)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::powerset::subsets;
+    use crate::truth_table::{equivalent, TruthTable};
+
+    #[test]
+    fn test_models() {
+        assert_eq!(
+            formula_models("AB|C&").unwrap(),
+            [vec!["B", "C"], vec!["A", "C"], vec!["A", "B", "C"]]
+        );
+        assert_eq!(formula_models("AA!&").unwrap(), Vec::<Vec<String>>::new());
+        assert_eq!(formula_models("1").unwrap(), [Vec::<String>::new()]);
+        assert_eq!(
+            formula_models("[x_10][x_2]>").unwrap(),
+            [vec![], vec!["x_2"], vec!["x_2", "x_10"]]
+        );
+    }
+
+    #[test]
+    fn test_family_formula() {
+        assert_eq!(
+            family_formula(&["A", "B"], &[vec!["A"], vec!["B"]]),
+            Ok("A!B&AB!&|".to_string())
+        );
+        assert_eq!(family_formula(&["A"], &[vec![]]), Ok("A!".to_string()));
+        assert_eq!(family_formula::<&str>(&["A"], &[]), Ok("0".to_string()));
+        assert_eq!(
+            family_formula(&["A", "B"], &[vec!["B", "A", "B"], vec!["A", "B"]]),
+            Ok("AB&".to_string())
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for expression in [
+            "AB|C&",
+            "AB^C=D>!",
+            "A1&B0|^",
+            "[beta][dark_mode]&[legacy]!>",
+        ] {
+            let formula = rpn_to_ast(expression).unwrap();
+            let table = TruthTable::from_formula(&formula).unwrap();
+            let family = models(&formula).unwrap();
+            let expected: Vec<Vec<String>> = table
+                .rows
+                .iter()
+                .filter(|row| row.output)
+                .map(|row| {
+                    table
+                        .variables
+                        .iter()
+                        .zip(&row.inputs)
+                        .filter(|&(_, &input)| input)
+                        .map(|(name, _)| name.clone())
+                        .collect()
+                })
+                .collect();
+            assert_eq!(family, expected, "{}", expression);
+
+            let rebuilt = family_to_formula(&table.variables, &family).unwrap();
+            assert_eq!(
+                TruthTable::from_formula(&rebuilt).unwrap(),
+                table,
+                "{}",
+                expression
+            );
+            assert_eq!(models(&rebuilt).unwrap(), family);
+        }
+    }
+
+    #[test]
+    fn test_from_powerset() {
+        let flags = ["beta", "dark_mode", "legacy", "search"];
+        // Exactly two flags enabled, never legacy together with beta.
+        let family: Vec<Vec<&str>> = subsets(&flags)
+            .unwrap()
+            .filter(|set| set.len() == 2 && !(set.contains(&"beta") && set.contains(&"legacy")))
+            .collect();
+        let formula = family_to_formula(&flags, &family).unwrap();
+        assert_eq!(TruthTable::from_formula(&formula).unwrap().count_ones(), 5);
+
+        let mut rebuilt = models(&formula).unwrap();
+        let mut family: Vec<Vec<String>> = family
+            .iter()
+            .map(|set| set.iter().map(|name| name.to_string()).collect())
+            .collect();
+        rebuilt.sort();
+        family.sort();
+        assert_eq!(rebuilt, family);
+
+        let all: Vec<Vec<&str>> = subsets(&flags).unwrap().collect();
+        let tautology = family_to_formula(&flags, &all).unwrap();
+        assert!(equivalent(&tautology, &Formula::Const(true)).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            family_to_formula(&["A", "B"], &[vec!["C"]]),
+            Err(FamilyError::UnknownElement("C".to_string()))
+        );
+        assert_eq!(
+            family_to_formula(&["A", "A"], &[]),
+            Err(FamilyError::DuplicateVariable("A".to_string()))
+        );
+        assert_eq!(
+            family_to_formula(&["dark-mode"], &[]),
+            Err(FamilyError::InvalidName("dark-mode".to_string()))
+        );
+        let expression: String =
+            (0..64).map(|i| format!("[x_{}]", i)).collect::<String>() + &"&".repeat(63);
+        assert_eq!(
+            formula_models(&expression),
+            Err(TruthTableError::TooManyVariables(64))
+        );
+    }
+}
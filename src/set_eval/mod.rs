@@ -0,0 +1,143 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+use crate::algebra::Sets;
+use crate::ast::{rpn_to_ast, Formula, ParseError};
+
+mod family;
+
+pub use family::{family_formula, family_to_formula, formula_models, models, FamilyError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetError {
+    Parse(ParseError),
+    // A variable other than A..Z, or a letter past the last given set.
+    UnboundVariable { name: String, sets: usize },
+}
+
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::Parse(err) => write!(f, "{}", err),
+            SetError::UnboundVariable { name, sets } => write!(
+                f,
+                "variable '{}' is not bound, {} sets are bound to A onwards",
+                name, sets
+            ),
+        }
+    }
+}
+
+impl Error for SetError {}
+
+impl From<ParseError> for SetError {
+    fn from(err: ParseError) -> Self {
+        SetError::Parse(err)
+    }
+}
+
+// Index of the set bound to a single letter variable, A being the first.
+fn set_index(name: &str) -> Option<usize> {
+    match name.as_bytes() {
+        &[letter] if letter.is_ascii_uppercase() => Some((letter - b'A') as usize),
+        _ => None,
+    }
+}
+
+// Evaluates the formula with variable A bound to `sets[0]`, B to `sets[1]`
+// and so on. Complements, and the constant 1, are relative to the union of
+// all the given sets. The result is sorted and deduplicated.
+pub fn evaluate_sets(formula: &Formula, sets: &[Vec<i32>]) -> Result<Vec<i32>, SetError> {
+    let mut vars = HashMap::new();
+    for name in formula.variables() {
+        let Some(set) = set_index(&name).and_then(|index| sets.get(index)) else {
+            return Err(SetError::UnboundVariable {
+                name,
+                sets: sets.len(),
+            });
+        };
+        vars.insert(name, set.iter().copied().collect::<BTreeSet<i32>>());
+    }
+    let algebra = Sets::new(sets.iter().flatten().copied().collect());
+    Ok(formula.evaluate_in(&algebra, &vars).into_iter().collect())
+}
+
+pub fn eval_set(expression: &str, sets: &[Vec<i32>]) -> Result<Vec<i32>, SetError> {
+    evaluate_sets(&rpn_to_ast(expression)?, sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operators() {
+        let sets = [vec![0, 1, 2], vec![0, 3, 4]];
+        assert_eq!(eval_set("AB&", &sets), Ok(vec![0]));
+        assert_eq!(eval_set("AB|", &sets), Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(eval_set("AB^", &sets), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(eval_set("AB>", &sets), Ok(vec![0, 3, 4]));
+        assert_eq!(eval_set("AB=", &sets), Ok(vec![0]));
+        assert_eq!(eval_set("A!", &sets), Ok(vec![3, 4]));
+        assert_eq!(eval_set("A!B!|", &sets), Ok(vec![1, 2, 3, 4]));
+        assert_eq!(eval_set("1", &sets), Ok(vec![0, 1, 2, 3, 4]));
+        assert_eq!(eval_set("A0|", &sets), Ok(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_subject_examples() {
+        assert_eq!(
+            eval_set("AB&", &[vec![0, 1, 2], vec![0, 3, 4]]),
+            Ok(vec![0])
+        );
+        assert_eq!(
+            eval_set("AB|", &[vec![0, 1, 2], vec![3, 4, 5]]),
+            Ok(vec![0, 1, 2, 3, 4, 5])
+        );
+        assert_eq!(eval_set("A!", &[vec![0, 1, 2]]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_sorted_and_deduplicated() {
+        let sets = [vec![5, -3, 5, 9], vec![9, 1, -3, 1], vec![7]];
+        assert_eq!(eval_set("AB|", &sets), Ok(vec![-3, 1, 5, 9]));
+        assert_eq!(eval_set("C!", &sets), Ok(vec![-3, 1, 5, 9]));
+        assert_eq!(eval_set("AB&C|", &sets), Ok(vec![-3, 7, 9]));
+    }
+
+    #[test]
+    fn test_laws() {
+        let sets = [vec![1, 2, 3, 4], vec![3, 4, 5, 6], vec![1, 4, 6, 8], vec![]];
+        for (left, right) in [
+            ("AB&!", "A!B!|"),
+            ("AB|C&", "AC&BC&|"),
+            ("AB>", "A!B|"),
+            ("AB=", "AB>BA>&"),
+            ("AA!|", "1"),
+            ("AD|", "A"),
+            ("D!", "1"),
+        ] {
+            assert_eq!(eval_set(left, &sets), eval_set(right, &sets), "{}", left);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            eval_set("AC&", &[vec![1], vec![2]]),
+            Err(SetError::UnboundVariable {
+                name: "C".to_string(),
+                sets: 2
+            })
+        );
+        assert!(matches!(
+            eval_set("A[x_1]&", &[vec![1], vec![2]]),
+            Err(SetError::UnboundVariable { .. })
+        ));
+        assert!(matches!(
+            eval_set("A&", &[vec![1]]),
+            Err(SetError::Parse(ParseError::StackUnderflow { .. }))
+        ));
+    }
+}
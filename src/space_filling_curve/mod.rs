@@ -0,0 +1,122 @@
+// Spreads the 16 bits of `value` to the even bit positions of a u32.
+fn spread(value: u16) -> u32 {
+    let mut bits = value as u32;
+    bits = (bits | bits << 8) & 0x00FF_00FF;
+    bits = (bits | bits << 4) & 0x0F0F_0F0F;
+    bits = (bits | bits << 2) & 0x3333_3333;
+    (bits | bits << 1) & 0x5555_5555
+}
+
+// Gathers the even bit positions of `bits` back into a u16.
+fn compact(bits: u32) -> u16 {
+    let mut bits = bits & 0x5555_5555;
+    bits = (bits | bits >> 1) & 0x3333_3333;
+    bits = (bits | bits >> 2) & 0x0F0F_0F0F;
+    bits = (bits | bits >> 4) & 0x00FF_00FF;
+    (bits | bits >> 8) as u16
+}
+
+// Position of (x, y) along the Z-order curve: x in the even bits, y in the
+// odd bits.
+pub fn z_index(x: u16, y: u16) -> u32 {
+    spread(x) | spread(y) << 1
+}
+
+pub fn z_coordinates(index: u32) -> (u16, u16) {
+    (compact(index), compact(index >> 1))
+}
+
+// The Z-order index scaled to [0, 1]. Every u32 is exact in an f64 and the
+// division is correctly rounded, so map(x, y) * u32::MAX lands within
+// 2^-20 of the index whatever the index is, and `reverse_map` rounds back to
+// it. The round trip therefore holds for every point, not just those tested.
+pub fn map(x: u16, y: u16) -> f64 {
+    z_index(x, y) as f64 / u32::MAX as f64
+}
+
+// Panics unless `n` is in [0, 1], which NaN never is.
+pub fn reverse_map(n: f64) -> (u16, u16) {
+    assert!(
+        (0.0..=1.0).contains(&n),
+        "reverse_map needs a value in [0, 1], got {}",
+        n
+    );
+    z_coordinates((n * u32::MAX as f64).round() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_z_index() {
+        assert_eq!(z_index(0, 0), 0);
+        assert_eq!(z_index(1, 0), 1);
+        assert_eq!(z_index(0, 1), 2);
+        assert_eq!(z_index(1, 1), 3);
+        assert_eq!(z_index(2, 0), 4);
+        assert_eq!(z_index(0b1010, 0b0110), 0b0110_1100);
+        assert_eq!(z_index(u16::MAX, 0), 0x5555_5555);
+        assert_eq!(z_index(u16::MAX, u16::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_map_bounds() {
+        assert_eq!(map(0, 0), 0.0);
+        assert_eq!(map(u16::MAX, u16::MAX), 1.0);
+        assert_eq!(reverse_map(0.0), (0, 0));
+        assert_eq!(reverse_map(1.0), (u16::MAX, u16::MAX));
+        assert!(map(1, 0) > 0.0 && map(1, 0) < map(0, 1));
+    }
+
+    #[test]
+    fn test_index_round_trip_per_coordinate() {
+        // Spread values only use the even bits, so x and y never overlap and
+        // z_index is a bijection.
+        for x in 0..=u16::MAX {
+            assert_eq!(spread(x) & !0x5555_5555, 0);
+            assert_eq!(compact(spread(x)), x);
+            assert_eq!(z_coordinates(z_index(x, !x)), (x, !x));
+        }
+    }
+
+    #[test]
+    fn test_map_round_trip() {
+        // Every x against a spread of y values and every y against a spread of
+        // x values. With y fixed, growing x grows the index, so the mapped
+        // values must increase too.
+        for fixed in (0..=u16::MAX).step_by(1021).chain([1, 2, 3, u16::MAX - 1]) {
+            let mut previous = -1.0;
+            for value in 0..=u16::MAX {
+                for (x, y) in [(value, fixed), (fixed, value)] {
+                    assert_eq!(reverse_map(map(x, y)), (x, y));
+                }
+                let n = map(value, fixed);
+                assert!((0.0..=1.0).contains(&n));
+                assert!(n > previous);
+                previous = n;
+            }
+        }
+
+        let mut previous = -1.0;
+        for index in (0..=u32::MAX).step_by(65_521).chain([u32::MAX]) {
+            let (x, y) = z_coordinates(index);
+            let n = map(x, y);
+            assert!(n > previous, "{} at index {}", n, index);
+            assert_eq!(reverse_map(n), (x, y));
+            previous = n;
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "reverse_map needs a value in [0, 1]")]
+    fn test_reverse_map_out_of_range() {
+        reverse_map(1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "reverse_map needs a value in [0, 1]")]
+    fn test_reverse_map_nan() {
+        reverse_map(f64::NAN);
+    }
+}
@@ -0,0 +1,206 @@
+use super::rows::{Op, Program};
+use super::{check_variables, TruthTableError};
+use crate::algebra::{BooleanAlgebra, Lanes};
+use crate::ast::{natural_cmp, Formula};
+
+// Lane i of a block holds row `64 * block + i`, so the variables mapped to
+// the six low bits of the row index follow fixed patterns.
+const PATTERNS: [u64; 6] = [
+    0xAAAA_AAAA_AAAA_AAAA,
+    0xCCCC_CCCC_CCCC_CCCC,
+    0xF0F0_F0F0_F0F0_F0F0,
+    0xFF00_FF00_FF00_FF00,
+    0xFFFF_0000_FFFF_0000,
+    0xFFFF_FFFF_0000_0000,
+];
+
+impl Program {
+    // Evaluates 64 assignments at once, bit i of `lanes[v]` being the value of
+    // variable v in assignment i.
+    pub(crate) fn eval_lanes(&self, lanes: &[u64], stack: &mut Vec<u64>) -> u64 {
+        stack.clear();
+        for &op in &self.ops {
+            let value = match op {
+                Op::Var(index) => lanes[index],
+                Op::Const(true) => Lanes.top(),
+                Op::Const(false) => Lanes.bottom(),
+                Op::Not => Lanes.complement(&stack.pop().unwrap()),
+                op => {
+                    let right = stack.pop().unwrap();
+                    let left = stack.pop().unwrap();
+                    op.apply(&Lanes, &left, &right)
+                }
+            };
+            stack.push(value);
+        }
+        stack.pop().expect("a formula leaves one value")
+    }
+}
+
+// A formula evaluated 64 truth table rows per pass, each variable being a
+// u64 whose bits are its values in consecutive rows.
+#[derive(Debug, Clone)]
+pub struct Bitsliced {
+    variables: Vec<String>,
+    program: Program,
+    lanes: Vec<u64>,
+    stack: Vec<u64>,
+}
+
+impl Bitsliced {
+    pub fn new(formula: &Formula) -> Result<Self, TruthTableError> {
+        Bitsliced::with_variables(formula, formula.variables())
+    }
+
+    // `variables` must contain those of the formula and may list more, which
+    // the formula then does not depend on.
+    pub fn with_variables(
+        formula: &Formula,
+        variables: Vec<String>,
+    ) -> Result<Self, TruthTableError> {
+        check_variables(&variables)?;
+        Ok(Bitsliced {
+            program: Program::compile(formula, &variables),
+            lanes: vec![0; variables.len()],
+            variables,
+            stack: Vec::new(),
+        })
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    pub fn num_blocks(&self) -> u64 {
+        1 << self.variables.len().saturating_sub(6)
+    }
+
+    // Evaluates arbitrary assignments given as one lane per variable.
+    pub fn eval(&mut self, lanes: &[u64]) -> u64 {
+        assert_eq!(lanes.len(), self.variables.len(), "one lane per variable");
+        self.program.eval_lanes(lanes, &mut self.stack)
+    }
+
+    // Outputs of rows `64 * index` to `64 * index + 63`, row `64 * index + i`
+    // in bit i. Bits past the last row of small tables are cleared.
+    pub fn block(&mut self, index: u64) -> u64 {
+        let num_vars = self.variables.len();
+        let base = index << 6;
+        for (var, lane) in self.lanes.iter_mut().enumerate() {
+            let bit = num_vars - 1 - var;
+            *lane = match PATTERNS.get(bit) {
+                Some(&pattern) => pattern,
+                None => 0u64.wrapping_sub(base >> bit & 1),
+            };
+        }
+        let word = self.program.eval_lanes(&self.lanes, &mut self.stack);
+        if num_vars < 6 {
+            word & ((1 << (1 << num_vars)) - 1)
+        } else {
+            word
+        }
+    }
+
+    pub fn count_models(&mut self) -> u64 {
+        (0..self.num_blocks())
+            .map(|index| self.block(index).count_ones() as u64)
+            .sum()
+    }
+}
+
+// Number of assignments of the formula's variables making it true.
+pub fn count_models(formula: &Formula) -> Result<u64, TruthTableError> {
+    Ok(Bitsliced::new(formula)?.count_models())
+}
+
+// Whether both formulas agree on every assignment of their variables.
+pub fn equivalent(left: &Formula, right: &Formula) -> Result<bool, TruthTableError> {
+    let mut variables = left.variables();
+    variables.extend(right.variables());
+    variables.sort_by(|a, b| natural_cmp(a, b));
+    variables.dedup();
+    let mut left = Bitsliced::with_variables(left, variables.clone())?;
+    let mut right = Bitsliced::with_variables(right, variables)?;
+    Ok((0..left.num_blocks()).all(|index| left.block(index) == right.block(index)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+    use crate::truth_table::assignments;
+
+    #[test]
+    fn test_blocks_match_evaluate() {
+        for expression in [
+            "AB&C|",
+            "AB^C=D>!",
+            "A1&B0|^",
+            "0",
+            "ABCDEFG&&&&&&H|",
+            "[x_1][x_2]^[x_3]>[x_4][x_5]=&[x_6][x_7]|^[x_8]!&",
+        ] {
+            let formula = rpn_to_ast(expression).unwrap();
+            let mut bits = Bitsliced::new(&formula).unwrap();
+            let variables = bits.variables().to_vec();
+            let blocks: Vec<u64> = (0..bits.num_blocks()).map(|i| bits.block(i)).collect();
+            for (row, vars) in assignments(&variables).enumerate() {
+                let output = blocks[row >> 6] >> (row & 63) & 1 == 1;
+                assert_eq!(output, formula.evaluate(&vars), "{} at {}", expression, row);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_lanes() {
+        let mut bits = Bitsliced::new(&rpn_to_ast("AB>").unwrap()).unwrap();
+        assert_eq!(bits.eval(&[0b1100, 0b1010]), !0b0100);
+    }
+
+    #[test]
+    fn test_count_models() {
+        assert_eq!(count_models(&rpn_to_ast("AB&C|").unwrap()).unwrap(), 5);
+        assert_eq!(count_models(&rpn_to_ast("1").unwrap()).unwrap(), 1);
+        assert_eq!(count_models(&rpn_to_ast("AA!&").unwrap()).unwrap(), 0);
+        let parity: String =
+            (0..20).map(|i| format!("[x_{}]", i)).collect::<String>() + &"^".repeat(19);
+        assert_eq!(
+            count_models(&rpn_to_ast(&parity).unwrap()).unwrap(),
+            1 << 19
+        );
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let equivalent_rpn = |left: &str, right: &str| {
+            equivalent(&rpn_to_ast(left).unwrap(), &rpn_to_ast(right).unwrap()).unwrap()
+        };
+        assert!(equivalent_rpn("AB>", "A!B|"));
+        assert!(equivalent_rpn("AB&!", "A!B!|"));
+        assert!(equivalent_rpn("AB=", "AB^!"));
+        assert!(equivalent_rpn("AA!|", "1"));
+        assert!(equivalent_rpn("AB&BA&|", "BA&"));
+        assert!(!equivalent_rpn("AB|", "AB^"));
+        assert!(!equivalent_rpn("A", "B"));
+        assert!(!equivalent_rpn("ABCDEFGH&&&&&&&", "ABCDEFG&&&&&&"));
+    }
+
+    #[test]
+    fn test_too_many_variables() {
+        let and_chain = |n: usize| {
+            let expression =
+                (0..n).map(|i| format!("[x_{}]", i)).collect::<String>() + &"&".repeat(n - 1);
+            rpn_to_ast(&expression).unwrap()
+        };
+        let error = TruthTableError::TooManyVariables(64);
+        assert_eq!(Bitsliced::new(&and_chain(64)).err(), Some(error.clone()));
+        assert_eq!(count_models(&and_chain(64)), Err(error.clone()));
+        assert_eq!(
+            equivalent(&and_chain(64), &and_chain(64)),
+            Err(error.clone())
+        );
+        // The limit applies to the variables of both formulas together.
+        let x_63 = Formula::var("x_63");
+        assert_eq!(equivalent(&and_chain(63), &x_63), Err(error));
+    }
+}
@@ -0,0 +1,393 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use super::TruthTable;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruthTableFormat {
+    // The `print_truth_table` layout, lines ending in `$`.
+    #[default]
+    Terminal,
+    Csv,
+    Markdown,
+    Json,
+    Latex,
+    Html,
+}
+
+impl TruthTableFormat {
+    pub const NAMES: [&'static str; 6] = ["terminal", "csv", "markdown", "json", "latex", "html"];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFormat(pub String);
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown format '{}', expected one of: {}",
+            self.0,
+            TruthTableFormat::NAMES.join(", ")
+        )
+    }
+}
+
+impl Error for UnknownFormat {}
+
+impl FromStr for TruthTableFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "terminal" => Ok(TruthTableFormat::Terminal),
+            "csv" => Ok(TruthTableFormat::Csv),
+            "markdown" | "md" => Ok(TruthTableFormat::Markdown),
+            "json" => Ok(TruthTableFormat::Json),
+            "latex" | "tex" => Ok(TruthTableFormat::Latex),
+            "html" => Ok(TruthTableFormat::Html),
+            _ => Err(UnknownFormat(name.to_string())),
+        }
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Names built directly rather than parsed need not be identifiers, so each
+// format escapes what is special to it.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn markdown_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+// Quotes a field per RFC 4180 when it holds a comma, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn latex_name(name: &str) -> String {
+    if name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return format!("${}$", name);
+    }
+    let mut escaped = String::new();
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\^{}"),
+            '~' => escaped.push_str("\\~{}"),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    format!("\\texttt{{{}}}", escaped)
+}
+
+fn input(variables: &[String], mask: u64, index: usize) -> u8 {
+    (mask >> (variables.len() - 1 - index) & 1) as u8
+}
+
+fn write_terminal<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    write!(out, "| ")?;
+    for var in variables {
+        write!(out, "{} | ", var)?;
+    }
+    writeln!(out, "= |$")?;
+    write!(out, "|")?;
+    for var in variables {
+        write!(out, "{}|", "-".repeat(var.chars().count() + 2))?;
+    }
+    writeln!(out, "---|$")?;
+    for (mask, output) in rows {
+        write!(out, "| ")?;
+        for (index, var) in variables.iter().enumerate() {
+            let width = var.chars().count();
+            write!(out, "{:<width$} | ", input(variables, mask, index))?;
+        }
+        writeln!(out, "{} |$", output as u8)?;
+    }
+    Ok(())
+}
+
+fn write_csv<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    for var in variables {
+        write!(out, "{},", csv_field(var))?;
+    }
+    // "=" like the other formats, as no variable can have that name.
+    writeln!(out, "=")?;
+    for (mask, output) in rows {
+        for index in 0..variables.len() {
+            write!(out, "{},", input(variables, mask, index))?;
+        }
+        writeln!(out, "{}", output as u8)?;
+    }
+    Ok(())
+}
+
+fn write_markdown<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    let names: Vec<String> = variables.iter().map(|var| markdown_escape(var)).collect();
+    write!(out, "|")?;
+    for name in &names {
+        write!(out, " {} |", name)?;
+    }
+    writeln!(out, " = |")?;
+    write!(out, "|")?;
+    for name in &names {
+        write!(out, "{}|", "-".repeat(name.chars().count() + 2))?;
+    }
+    writeln!(out, "---|")?;
+    for (mask, output) in rows {
+        write!(out, "|")?;
+        for (index, name) in names.iter().enumerate() {
+            let width = name.chars().count();
+            write!(out, " {:<width$} |", input(variables, mask, index))?;
+        }
+        writeln!(out, " {} |", output as u8)?;
+    }
+    Ok(())
+}
+
+fn write_json<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    let names: Vec<String> = variables.iter().map(|var| json_string(var)).collect();
+    writeln!(out, "{{")?;
+    writeln!(out, "  \"variables\": [{}],", names.join(", "))?;
+    write!(out, "  \"rows\": [")?;
+    for (count, (mask, output)) in rows.enumerate() {
+        let inputs: Vec<&str> = (0..variables.len())
+            .map(|index| {
+                if input(variables, mask, index) == 1 {
+                    "true"
+                } else {
+                    "false"
+                }
+            })
+            .collect();
+        write!(
+            out,
+            "{}\n    {{\"inputs\": [{}], \"output\": {}}}",
+            if count > 0 { "," } else { "" },
+            inputs.join(", "),
+            output
+        )?;
+    }
+    writeln!(out, "\n  ]")?;
+    writeln!(out, "}}")
+}
+
+fn write_latex<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\\begin{{tabular}}{{{}|c}}",
+        "c".repeat(variables.len())
+    )?;
+    let header: Vec<String> = variables.iter().map(|var| latex_name(var)).collect();
+    if header.is_empty() {
+        writeln!(out, "$=$ \\\\")?;
+    } else {
+        writeln!(out, "{} & $=$ \\\\", header.join(" & "))?;
+    }
+    writeln!(out, "\\hline")?;
+    for (mask, output) in rows {
+        for index in 0..variables.len() {
+            write!(out, "{} & ", input(variables, mask, index))?;
+        }
+        writeln!(out, "{} \\\\", output as u8)?;
+    }
+    writeln!(out, "\\end{{tabular}}")
+}
+
+fn write_html<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(out, "<table class=\"truth-table\">")?;
+    write!(out, "<thead><tr>")?;
+    for var in variables {
+        write!(out, "<th>{}</th>", html_escape(var))?;
+    }
+    writeln!(out, "<th>=</th></tr></thead>")?;
+    writeln!(out, "<tbody>")?;
+    for (mask, output) in rows {
+        write!(out, "<tr>")?;
+        for index in 0..variables.len() {
+            write!(out, "<td>{}</td>", input(variables, mask, index))?;
+        }
+        writeln!(out, "<td>{}</td></tr>", output as u8)?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")
+}
+
+// Rows are `(assignment, output)` pairs, the first variable being the most
+// significant bit of the assignment.
+pub(crate) fn write_table<W: Write>(
+    variables: &[String],
+    rows: impl Iterator<Item = (u64, bool)>,
+    format: TruthTableFormat,
+    out: &mut W,
+) -> io::Result<()> {
+    match format {
+        TruthTableFormat::Terminal => write_terminal(variables, rows, out),
+        TruthTableFormat::Csv => write_csv(variables, rows, out),
+        TruthTableFormat::Markdown => write_markdown(variables, rows, out),
+        TruthTableFormat::Json => write_json(variables, rows, out),
+        TruthTableFormat::Latex => write_latex(variables, rows, out),
+        TruthTableFormat::Html => write_html(variables, rows, out),
+    }
+}
+
+impl TruthTable {
+    pub fn write<W: Write>(&self, format: TruthTableFormat, out: &mut W) -> io::Result<()> {
+        let rows = self.rows.iter().map(|row| (row.mask(), row.output));
+        write_table(&self.variables, rows, format, out)
+    }
+
+    pub fn render(&self, format: TruthTableFormat) -> String {
+        let mut out = Vec::new();
+        self.write(format, &mut out)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(out).expect("truth tables render as UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Formula;
+    use crate::truth_table::{truth_table, TruthTable};
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("csv".parse(), Ok(TruthTableFormat::Csv));
+        assert_eq!("Markdown".parse(), Ok(TruthTableFormat::Markdown));
+        assert_eq!("tex".parse(), Ok(TruthTableFormat::Latex));
+        for name in TruthTableFormat::NAMES {
+            assert!(name.parse::<TruthTableFormat>().is_ok());
+        }
+        assert_eq!(
+            "yaml".parse::<TruthTableFormat>(),
+            Err(UnknownFormat("yaml".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_formats() {
+        let table = truth_table("A[x_1]&").unwrap();
+        assert_eq!(table.render(TruthTableFormat::Terminal), table.to_string());
+        assert_eq!(
+            table.render(TruthTableFormat::Terminal),
+            "| A | x_1 | = |$\n|---|-----|---|$\n| 0 | 0   | 0 |$\n| 0 | 1   | 0 |$\n\
+             | 1 | 0   | 0 |$\n| 1 | 1   | 1 |$\n"
+        );
+        assert_eq!(
+            table.render(TruthTableFormat::Csv),
+            "A,x_1,=\n0,0,0\n0,1,0\n1,0,0\n1,1,1\n"
+        );
+        assert_eq!(
+            table.render(TruthTableFormat::Markdown),
+            "| A | x_1 | = |\n|---|-----|---|\n| 0 | 0   | 0 |\n| 0 | 1   | 0 |\n\
+             | 1 | 0   | 0 |\n| 1 | 1   | 1 |\n"
+        );
+        assert_eq!(
+            table.render(TruthTableFormat::Json),
+            "{\n  \"variables\": [\"A\", \"x_1\"],\n  \"rows\": [\n\
+             \x20   {\"inputs\": [false, false], \"output\": false},\n\
+             \x20   {\"inputs\": [false, true], \"output\": false},\n\
+             \x20   {\"inputs\": [true, false], \"output\": false},\n\
+             \x20   {\"inputs\": [true, true], \"output\": true}\n  ]\n}\n"
+        );
+        assert_eq!(
+            table.render(TruthTableFormat::Latex),
+            "\\begin{tabular}{cc|c}\n$A$ & \\texttt{x\\_1} & $=$ \\\\\n\\hline\n\
+             0 & 0 & 0 \\\\\n0 & 1 & 0 \\\\\n1 & 0 & 0 \\\\\n1 & 1 & 1 \\\\\n\\end{tabular}\n"
+        );
+        assert_eq!(
+            table.render(TruthTableFormat::Html),
+            "<table class=\"truth-table\">\n<thead><tr><th>A</th><th>x_1</th><th>=</th></tr></thead>\n\
+             <tbody>\n<tr><td>0</td><td>0</td><td>0</td></tr>\n<tr><td>0</td><td>1</td><td>0</td></tr>\n\
+             <tr><td>1</td><td>0</td><td>0</td></tr>\n<tr><td>1</td><td>1</td><td>1</td></tr>\n\
+             </tbody>\n</table>\n"
+        );
+        let table = truth_table("[result]!").unwrap();
+        assert_eq!(table.render(TruthTableFormat::Csv), "result,=\n0,1\n1,0\n");
+    }
+
+    #[test]
+    fn test_escaped_names() {
+        let formula = Formula::Or(
+            Box::new(Formula::var("a<b")),
+            Box::new(Formula::var("c|d,\"e\"&%")),
+        );
+        let table = TruthTable::from_formula(&formula).unwrap();
+        assert_eq!(
+            table.render(TruthTableFormat::Csv),
+            "a<b,\"c|d,\"\"e\"\"&%\",=\n0,0,0\n0,1,1\n1,0,1\n1,1,1\n"
+        );
+        let markdown = table.render(TruthTableFormat::Markdown);
+        assert!(markdown.starts_with("| a<b | c\\|d,\"e\"&% | = |\n|-----|------------|---|\n"));
+        let html = table.render(TruthTableFormat::Html);
+        assert!(html.contains("<th>a&lt;b</th><th>c|d,\"e\"&amp;%</th><th>=</th>"));
+        let latex = table.render(TruthTableFormat::Latex);
+        assert!(latex.contains("\\texttt{a<b} & \\texttt{c|d,\"e\"\\&\\%} & $=$"));
+        assert_eq!(
+            latex_name("x^2~\\"),
+            "\\texttt{x\\^{}2\\~{}\\textbackslash{}}"
+        );
+    }
+
+    #[test]
+    fn test_constant_table() {
+        let table = truth_table("1").unwrap();
+        assert_eq!(table.render(TruthTableFormat::Csv), "=\n1\n");
+        assert_eq!(
+            table.render(TruthTableFormat::Latex),
+            "\\begin{tabular}{|c}\n$=$ \\\\\n\\hline\n1 \\\\\n\\end{tabular}\n"
+        );
+    }
+}
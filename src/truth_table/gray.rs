@@ -0,0 +1,193 @@
+use super::rows::{Op, Program};
+use super::{check_variables, TruthTableError};
+use crate::algebra::{BooleanAlgebra, Bools};
+use crate::ast::Formula;
+
+// The formula as a tree of cached node values. Flipping a variable only
+// recomputes the ancestors of its occurrences, stopping at the first node
+// whose value does not change.
+#[derive(Debug, Clone)]
+struct Circuit {
+    ops: Vec<Op>,
+    children: Vec<(usize, usize)>,
+    parents: Vec<Option<usize>>,
+    values: Vec<bool>,
+    occurrences: Vec<Vec<usize>>,
+}
+
+impl Circuit {
+    fn new(program: &Program) -> Self {
+        let len = program.ops.len();
+        let mut children = vec![(0, 0); len];
+        let mut parents = vec![None; len];
+        let mut occurrences = vec![Vec::new(); program.num_vars];
+        let mut stack: Vec<usize> = Vec::new();
+        for (index, &op) in program.ops.iter().enumerate() {
+            match op {
+                Op::Var(var) => occurrences[var].push(index),
+                Op::Const(_) => {}
+                Op::Not => {
+                    let child = stack.pop().unwrap();
+                    children[index] = (child, child);
+                    parents[child] = Some(index);
+                }
+                _ => {
+                    let right = stack.pop().unwrap();
+                    let left = stack.pop().unwrap();
+                    children[index] = (left, right);
+                    parents[left] = Some(index);
+                    parents[right] = Some(index);
+                }
+            }
+            stack.push(index);
+        }
+
+        let mut circuit = Circuit {
+            ops: program.ops.clone(),
+            children,
+            parents,
+            values: vec![false; len],
+            occurrences,
+        };
+        // Postfix order puts every node after its children.
+        for index in 0..len {
+            circuit.values[index] = circuit.compute(index);
+        }
+        circuit
+    }
+
+    fn compute(&self, index: usize) -> bool {
+        let (left, right) = self.children[index];
+        match self.ops[index] {
+            Op::Var(_) => self.values[index],
+            Op::Const(val) => val,
+            Op::Not => Bools.complement(&self.values[left]),
+            op => op.apply(&Bools, &self.values[left], &self.values[right]),
+        }
+    }
+
+    fn flip(&mut self, var: usize) {
+        for i in 0..self.occurrences[var].len() {
+            let mut node = self.occurrences[var][i];
+            self.values[node] = !self.values[node];
+            while let Some(parent) = self.parents[node] {
+                let value = self.compute(parent);
+                if value == self.values[parent] {
+                    break;
+                }
+                self.values[parent] = value;
+                node = parent;
+            }
+        }
+    }
+
+    fn output(&self) -> bool {
+        *self.values.last().expect("a formula has at least one node")
+    }
+}
+
+// Truth table rows in Gray-code order: row k has the assignment
+// `k ^ k >> 1`, so successive assignments differ in exactly one variable.
+// Masks use the same bit order as `Rows`.
+#[derive(Debug, Clone)]
+pub struct GrayRows {
+    variables: Vec<String>,
+    circuit: Circuit,
+    next: u64,
+    end: u64,
+}
+
+impl GrayRows {
+    pub fn new(formula: &Formula) -> Result<Self, TruthTableError> {
+        let variables = formula.variables();
+        check_variables(&variables)?;
+        Ok(GrayRows {
+            circuit: Circuit::new(&Program::compile(formula, &variables)),
+            end: 1 << variables.len(),
+            variables,
+            next: 0,
+        })
+    }
+
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+}
+
+impl Iterator for GrayRows {
+    type Item = (u64, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        let index = self.next;
+        self.next += 1;
+        if index > 0 {
+            // Going from Gray code k - 1 to Gray code k flips the bit at the
+            // position of the lowest set bit of k.
+            let bit = index.trailing_zeros() as usize;
+            self.circuit.flip(self.variables.len() - 1 - bit);
+        }
+        Some((index ^ index >> 1, self.circuit.output()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.end - self.next).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+pub fn gray_rows(formula: &Formula) -> Result<GrayRows, TruthTableError> {
+    GrayRows::new(formula)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+    use crate::truth_table::rows;
+
+    #[test]
+    fn test_gray_order() {
+        let masks: Vec<u64> = gray_rows(&rpn_to_ast("AB|C|").unwrap())
+            .unwrap()
+            .map(|(mask, _)| mask)
+            .collect();
+        assert_eq!(masks, [0, 1, 3, 2, 6, 7, 5, 4]);
+        for pair in masks.windows(2) {
+            assert_eq!((pair[0] ^ pair[1]).count_ones(), 1);
+        }
+    }
+
+    #[test]
+    fn test_matches_binary_rows() {
+        for expression in [
+            "AB&C|",
+            "AB^C=D>!",
+            "A1&B0|^",
+            "1",
+            "AA!&",
+            "AB&A!B!&|C^CD>&",
+            "[x_10][x_2]>[x_1]&[x_10]!|",
+        ] {
+            let formula = rpn_to_ast(expression).unwrap();
+            let binary: Vec<bool> = rows(&formula).unwrap().map(|(_, output)| output).collect();
+            let rows = gray_rows(&formula).unwrap();
+            assert_eq!(rows.size_hint().1, Some(binary.len()));
+            for (mask, output) in rows {
+                assert_eq!(output, binary[mask as usize], "{} at {}", expression, mask);
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_variables() {
+        let expression: String =
+            (0..40).map(|i| format!("[x_{}]", i)).collect::<String>() + &"|".repeat(39);
+        let rows = gray_rows(&rpn_to_ast(&expression).unwrap()).unwrap();
+        assert_eq!(rows.size_hint(), (1 << 40, Some(1 << 40)));
+        let rows: Vec<(u64, bool)> = rows.take(4).collect();
+        assert_eq!(rows, [(0, false), (1, true), (3, true), (2, true)]);
+    }
+}
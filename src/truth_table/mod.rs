@@ -1,146 +1,165 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
-#[derive(Debug, Clone)]
-enum ASTNode {
-    Const(char),
-    Not(Box<ASTNode>),
-    And(Box<ASTNode>, Box<ASTNode>),
-    Or(Box<ASTNode>, Box<ASTNode>),
-    Xor(Box<ASTNode>, Box<ASTNode>),
-    Implies(Box<ASTNode>, Box<ASTNode>),
-    Equiv(Box<ASTNode>, Box<ASTNode>),
+use std::error::Error;
+use std::fmt;
+
+use crate::ast::{rpn_to_ast, Formula, ParseError};
+
+mod bitslice;
+mod format;
+mod gray;
+mod rows;
+
+pub use bitslice::{count_models, equivalent, Bitsliced};
+pub use format::{TruthTableFormat, UnknownFormat};
+pub use gray::{gray_rows, GrayRows};
+pub use rows::{rows, write_truth_table, write_truth_table_with, Rows};
+
+// Rows are indexed by u64 masks, which leaves room for 63 variables.
+pub const MAX_VARIABLES: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TruthTableError {
+    Parse(ParseError),
+    TooManyVariables(usize),
 }
 
-impl ASTNode {
-    fn evaluate(&self, vars: &HashMap<char, bool>) -> bool {
+impl fmt::Display for TruthTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ASTNode::Const(val) => *vars.get(val).unwrap_or(&false),
-            ASTNode::Not(expr) => !expr.evaluate(vars),
-            ASTNode::And(left, right) => left.evaluate(vars) && right.evaluate(vars),
-            ASTNode::Or(left, right) => left.evaluate(vars) || right.evaluate(vars),
-            ASTNode::Xor(left, right) => left.evaluate(vars) != right.evaluate(vars),
-            ASTNode::Implies(left, right) => !left.evaluate(vars) || right.evaluate(vars),
-            ASTNode::Equiv(left, right) => left.evaluate(vars) == right.evaluate(vars),
+            TruthTableError::Parse(err) => write!(f, "{}", err),
+            TruthTableError::TooManyVariables(count) => write!(
+                f,
+                "truth tables are limited to {} variables, the formula has {}",
+                MAX_VARIABLES, count
+            ),
         }
     }
 }
 
-fn rpn_to_ast(expression: &str) -> Option<ASTNode> {
-    let mut stack: VecDeque<ASTNode> = VecDeque::new();
-
-    for char in expression.chars() {
-        match char {
-            'A'..='Z' => stack.push_back(ASTNode::Const(char)),
-            '!' => {
-                if let Some(expr) = stack.pop_back() {
-                    stack.push_back(ASTNode::Not(Box::new(expr)));
-                } else {
-                    return None;
-                }
-            }
-            '&' | '|' | '^' | '>' | '=' => {
-                if let (Some(right), Some(left)) = (stack.pop_back(), stack.pop_back()) {
-                    let node = match char {
-                        '&' => ASTNode::And(Box::new(left), Box::new(right)),
-                        '|' => ASTNode::Or(Box::new(left), Box::new(right)),
-                        '^' => ASTNode::Xor(Box::new(left), Box::new(right)),
-                        '>' => ASTNode::Implies(Box::new(left), Box::new(right)),
-                        '=' => ASTNode::Equiv(Box::new(left), Box::new(right)),
-                        _ => unreachable!(),
-                    };
-                    stack.push_back(node);
-                } else {
-                    return None;
-                }
-            }
-            _ => return None,
-        }
-    }
-    if stack.len() == 1 {
-        stack.pop_back()
-    } else {
-        None
+impl Error for TruthTableError {}
+
+impl From<ParseError> for TruthTableError {
+    fn from(err: ParseError) -> Self {
+        TruthTableError::Parse(err)
     }
 }
 
-impl PartialEq for ASTNode {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (ASTNode::Const(a), ASTNode::Const(b)) => a == b,
-            (ASTNode::Not(a), ASTNode::Not(b)) => a == b,
-            (ASTNode::And(a1, a2), ASTNode::And(b1, b2))
-            | (ASTNode::Or(a1, a2), ASTNode::Or(b1, b2))
-            | (ASTNode::Xor(a1, a2), ASTNode::Xor(b1, b2))
-            | (ASTNode::Implies(a1, a2), ASTNode::Implies(b1, b2))
-            | (ASTNode::Equiv(a1, a2), ASTNode::Equiv(b1, b2)) => a1 == b1 && a2 == b2,
-            _ => false,
-        }
+pub(crate) fn check_variables(variables: &[String]) -> Result<(), TruthTableError> {
+    if variables.len() > MAX_VARIABLES {
+        return Err(TruthTableError::TooManyVariables(variables.len()));
     }
+    Ok(())
 }
 
-fn extract_variables(expression: &str) -> Vec<char> {
-    let mut variables = HashSet::new();
-    for char in expression.chars() {
-        if char.is_ascii_uppercase() {
-            variables.insert(char);
-        }
-    }
-    let mut variables: Vec<char> = variables.into_iter().collect();
-    variables.sort();
-    variables
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowOrder {
+    // Assignments counted up as binary numbers.
+    #[default]
+    Binary,
+    // Successive assignments differ in a single variable.
+    Gray,
 }
 
-fn generate_var_combinations(variables: &[char]) -> Vec<HashMap<char, bool>> {
-    let num_vars = variables.len();
-    let num_combinations = 1 << num_vars;
-    let mut combinations = Vec::new();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub inputs: Vec<bool>,
+    pub output: bool,
+}
 
-    for combination_index in 0..num_combinations {
-        let mut var_values = HashMap::new();
-        for (bit_pos, var) in variables.iter().enumerate() {
-            let val = (combination_index >> (num_vars - 1 - bit_pos)) & 1 != 0;
-            var_values.insert(*var, val);
-        }
-        combinations.push(var_values);
+impl Row {
+    // The inputs as a bitmask, the first variable being the most significant bit.
+    pub fn mask(&self) -> u64 {
+        self.inputs
+            .iter()
+            .fold(0, |mask, &value| mask << 1 | value as u64)
     }
-    combinations
 }
 
-fn print_header(variables: &[char]) {
-    print!("| ");
-    for var in variables {
-        print!("{} | ", var);
+// Rows are ordered by the inputs read as a binary number, with the first
+// variable as the most significant bit, unless built in `RowOrder::Gray`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable {
+    pub variables: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+impl TruthTable {
+    pub fn from_formula(formula: &Formula) -> Result<Self, TruthTableError> {
+        TruthTable::from_formula_with(formula, RowOrder::Binary)
+    }
+
+    pub fn from_formula_with(formula: &Formula, order: RowOrder) -> Result<Self, TruthTableError> {
+        let variables = formula.variables();
+        let rows: Box<dyn Iterator<Item = (u64, bool)>> = match order {
+            RowOrder::Binary => Box::new(Rows::new(formula)?),
+            RowOrder::Gray => Box::new(GrayRows::new(formula)?),
+        };
+        let rows = rows
+            .map(|(mask, output)| Row {
+                inputs: (0..variables.len())
+                    .map(|index| mask >> (variables.len() - 1 - index) & 1 == 1)
+                    .collect(),
+                output,
+            })
+            .collect();
+        Ok(TruthTable { variables, rows })
+    }
+
+    pub fn outputs(&self) -> Vec<bool> {
+        self.rows.iter().map(|row| row.output).collect()
     }
-    println!("= |$");
 
-    print!("|");
-    for _ in variables {
-        print!("---|");
+    pub fn count_ones(&self) -> usize {
+        self.rows.iter().filter(|row| row.output).count()
+    }
+
+    // Assignments evaluating to true, in increasing order.
+    pub fn minterms(&self) -> Vec<u64> {
+        let mut minterms: Vec<u64> = self
+            .rows
+            .iter()
+            .filter(|row| row.output)
+            .map(Row::mask)
+            .collect();
+        minterms.sort_unstable();
+        minterms
     }
-    println!("---|$");
 }
 
-fn print_truth_table_rows(vars: &[char], ast: &ASTNode, combs: Vec<HashMap<char, bool>>) {
-    for var_values in combs {
-        let result = ast.evaluate(&var_values);
-        print!("| ");
-        for var in vars {
-            print!("{} | ", if var_values[var] { 1 } else { 0 });
-        }
-        println!("{} |$", if result { 1 } else { 0 });
+// The layout of `print_truth_table`, with every line ending in `$`.
+impl fmt::Display for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(TruthTableFormat::Terminal))
     }
 }
 
-pub fn print_truth_table(expression: &str) {
-    let variables = extract_variables(expression);
-    let combinations = generate_var_combinations(&variables);
+pub fn truth_table(expression: &str) -> Result<TruthTable, TruthTableError> {
+    TruthTable::from_formula(&rpn_to_ast(expression)?)
+}
 
-    if let Some(ast) = rpn_to_ast(expression) {
-        print_header(&variables);
-        print_truth_table_rows(&variables, &ast, combinations);
-    } else {
-        println!("Invalid expression");
-    }
+pub fn truth_table_with(expression: &str, order: RowOrder) -> Result<TruthTable, TruthTableError> {
+    TruthTable::from_formula_with(&rpn_to_ast(expression)?, order)
+}
+
+pub fn print_truth_table(expression: &str) -> Result<(), TruthTableError> {
+    print!("{}", truth_table(expression)?);
+    Ok(())
+}
+
+// Every assignment of `variables` in truth table order, the first variable
+// being the most significant bit of the row index. Tests check the fast
+// evaluators against `Formula::evaluate` with it.
+#[cfg(test)]
+pub(crate) fn assignments(
+    variables: &[String],
+) -> impl Iterator<Item = std::collections::HashMap<String, bool>> + '_ {
+    let n = variables.len();
+    (0..1u64 << n).map(move |row| {
+        variables
+            .iter()
+            .enumerate()
+            .map(|(i, var)| (var.clone(), row >> (n - 1 - i) & 1 == 1))
+            .collect()
+    })
 }
 
 #[cfg(test)]
@@ -148,25 +167,80 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_rpn_to_ast() {
+    fn test_extract_variables() {
         let expression = "AB&C|";
+        let variables = rpn_to_ast(expression).unwrap().variables();
+        let expected_vars = vec!["A", "B", "C"];
+        assert_eq!(variables, expected_vars);
+    }
+
+    #[test]
+    fn test_truth_table() {
+        let table = truth_table("AB&C|").unwrap();
+        assert_eq!(table.variables, ["A", "B", "C"]);
+        assert_eq!(table.rows.len(), 8);
+        assert_eq!(
+            table.rows[6],
+            Row {
+                inputs: vec![true, true, false],
+                output: true
+            }
+        );
+        assert_eq!(
+            table.outputs(),
+            [false, true, false, true, false, true, true, true]
+        );
+        assert_eq!(table.count_ones(), 5);
+        assert_eq!(table.minterms(), [1, 3, 5, 6, 7]);
+        assert_eq!(truth_table("CAB&|").unwrap().outputs(), table.outputs());
+        assert_ne!(truth_table("AB|C|").unwrap(), table);
+
+        let gray = truth_table_with("AB&C|", RowOrder::Gray).unwrap();
+        assert_eq!(gray.rows[3].inputs, [false, true, false]);
+        assert_eq!(gray.rows[3].mask(), 2);
+        assert_eq!(gray.minterms(), table.minterms());
+        assert_eq!(gray.count_ones(), table.count_ones());
+
+        let table = truth_table("1").unwrap();
+        assert!(table.variables.is_empty());
+        assert_eq!(table.outputs(), [true]);
+    }
 
-        let ast = rpn_to_ast(expression).unwrap();
-        let expected_ast = ASTNode::Or(
-            Box::new(ASTNode::And(
-                Box::new(ASTNode::Const('A')),
-                Box::new(ASTNode::Const('B')),
-            )),
-            Box::new(ASTNode::Const('C')),
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            truth_table("[x_1]B^").unwrap().to_string(),
+            "| B | x_1 | = |$\n|---|-----|---|$\n| 0 | 0   | 0 |$\n| 0 | 1   | 1 |$\n\
+             | 1 | 0   | 1 |$\n| 1 | 1   | 0 |$\n"
         );
-        assert_eq!(ast, expected_ast);
     }
 
     #[test]
-    fn test_extract_variables() {
-        let expression = "AB&C|";
-        let variables = extract_variables(expression);
-        let expected_vars = vec!['A', 'B', 'C'];
-        assert_eq!(variables, expected_vars);
+    fn test_too_many_variables() {
+        let or_chain = |n: usize| {
+            (0..n).map(|i| format!("[x_{}]", i)).collect::<String>() + &"|".repeat(n - 1)
+        };
+        let formula = rpn_to_ast(&or_chain(64)).unwrap();
+        let error = TruthTableError::TooManyVariables(64);
+        assert_eq!(truth_table(&or_chain(64)), Err(error.clone()));
+        assert_eq!(
+            TruthTable::from_formula_with(&formula, RowOrder::Gray),
+            Err(error.clone())
+        );
+        assert_eq!(rows(&formula).err(), Some(error.clone()));
+        assert_eq!(gray_rows(&formula).err(), Some(error.clone()));
+        let written = write_truth_table(&formula, TruthTableFormat::Csv, &mut Vec::new());
+        assert_eq!(
+            written.unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            error.to_string(),
+            "truth tables are limited to 63 variables, the formula has 64"
+        );
+
+        let formula = rpn_to_ast(&or_chain(63)).unwrap();
+        assert_eq!(rows(&formula).unwrap().size_hint().1, Some(1 << 63));
+        assert!(matches!(truth_table("A|"), Err(TruthTableError::Parse(_))));
     }
 }
@@ -0,0 +1,241 @@
+use std::io::{self, Write};
+
+use super::bitslice::Bitsliced;
+use super::format::write_table;
+use super::gray::GrayRows;
+use super::{RowOrder, TruthTableError, TruthTableFormat};
+use crate::algebra::BooleanAlgebra;
+use crate::ast::Formula;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Op {
+    Var(usize),
+    Const(bool),
+    Not,
+    And,
+    Or,
+    Xor,
+    Implies,
+    Equiv,
+}
+
+impl Op {
+    pub(super) fn apply<A: BooleanAlgebra>(
+        self,
+        algebra: &A,
+        left: &A::Element,
+        right: &A::Element,
+    ) -> A::Element {
+        match self {
+            Op::And => algebra.meet(left, right),
+            Op::Or => algebra.join(left, right),
+            Op::Xor => algebra.xor(left, right),
+            Op::Implies => algebra.implies(left, right),
+            Op::Equiv => algebra.equiv(left, right),
+            _ => unreachable!("not a binary operator"),
+        }
+    }
+}
+
+// A formula flattened to postfix operations over variable indices, so that it
+// can be evaluated on a bitmask without building a map per row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Program {
+    pub(super) ops: Vec<Op>,
+    pub(super) num_vars: usize,
+}
+
+impl Program {
+    pub(crate) fn compile(formula: &Formula, variables: &[String]) -> Self {
+        let mut ops = Vec::new();
+        Self::emit(formula, variables, &mut ops);
+        Program {
+            ops,
+            num_vars: variables.len(),
+        }
+    }
+
+    fn emit(node: &Formula, variables: &[String], ops: &mut Vec<Op>) {
+        let mut binary = |left: &Formula, right: &Formula, op: Op| {
+            Self::emit(left, variables, ops);
+            Self::emit(right, variables, ops);
+            ops.push(op);
+        };
+        match node {
+            Formula::Var(name) => {
+                let index = variables
+                    .iter()
+                    .position(|var| var == name)
+                    .expect("every variable of the formula has an index");
+                ops.push(Op::Var(index));
+            }
+            Formula::Const(val) => ops.push(Op::Const(*val)),
+            Formula::Not(inner) => {
+                Self::emit(inner, variables, ops);
+                ops.push(Op::Not);
+            }
+            Formula::And(left, right) => binary(left, right, Op::And),
+            Formula::Or(left, right) => binary(left, right, Op::Or),
+            Formula::Xor(left, right) => binary(left, right, Op::Xor),
+            Formula::Implies(left, right) => binary(left, right, Op::Implies),
+            Formula::Equiv(left, right) => binary(left, right, Op::Equiv),
+        }
+    }
+}
+
+// Lazily evaluated rows of a truth table, as `(assignment, output)` pairs in
+// increasing order of the assignment bitmask. The first variable is the most
+// significant bit, so the mask of a row is also its index. Outputs are
+// computed 64 rows at a time.
+#[derive(Debug, Clone)]
+pub struct Rows {
+    bits: Bitsliced,
+    block: u64,
+    next: u64,
+    end: u64,
+}
+
+impl Rows {
+    pub fn new(formula: &Formula) -> Result<Self, TruthTableError> {
+        let bits = Bitsliced::new(formula)?;
+        Ok(Rows {
+            end: 1 << bits.variables().len(),
+            bits,
+            block: 0,
+            next: 0,
+        })
+    }
+
+    pub fn variables(&self) -> &[String] {
+        self.bits.variables()
+    }
+
+    // Value of variable `index` in an assignment yielded by this iterator.
+    pub fn input(&self, mask: u64, index: usize) -> bool {
+        mask >> (self.variables().len() - 1 - index) & 1 == 1
+    }
+}
+
+impl Iterator for Rows {
+    type Item = (u64, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        let mask = self.next;
+        if mask & 63 == 0 {
+            self.block = self.bits.block(mask >> 6);
+        }
+        self.next += 1;
+        Some((mask, self.block >> (mask & 63) & 1 == 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.end - self.next).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+pub fn rows(formula: &Formula) -> Result<Rows, TruthTableError> {
+    Rows::new(formula)
+}
+
+// Writes the truth table row by row, never holding more than one row.
+pub fn write_truth_table<W: Write>(
+    formula: &Formula,
+    format: TruthTableFormat,
+    out: &mut W,
+) -> io::Result<()> {
+    write_truth_table_with(formula, format, RowOrder::Binary, out)
+}
+
+pub fn write_truth_table_with<W: Write>(
+    formula: &Formula,
+    format: TruthTableFormat,
+    order: RowOrder,
+    out: &mut W,
+) -> io::Result<()> {
+    let variables = formula.variables();
+    let invalid = |err| io::Error::new(io::ErrorKind::InvalidInput, err);
+    match order {
+        RowOrder::Binary => write_table(
+            &variables,
+            Rows::new(formula).map_err(invalid)?,
+            format,
+            out,
+        ),
+        RowOrder::Gray => write_table(
+            &variables,
+            GrayRows::new(formula).map_err(invalid)?,
+            format,
+            out,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::rpn_to_ast;
+    use crate::truth_table::assignments;
+
+    #[test]
+    fn test_row_order() {
+        let formula = rpn_to_ast("A[x_1]|").unwrap();
+        let rows = rows(&formula).unwrap();
+        assert_eq!(rows.variables(), ["A", "x_1"]);
+        let inputs: Vec<(bool, bool)> = rows
+            .clone()
+            .map(|(mask, _)| (rows.input(mask, 0), rows.input(mask, 1)))
+            .collect();
+        assert_eq!(
+            inputs,
+            vec![(false, false), (false, true), (true, false), (true, true)]
+        );
+    }
+
+    #[test]
+    fn test_matches_evaluate() {
+        for expression in ["AB&C|", "AB^C=D>!", "A1&B0|^", "1", "[x_10][x_2]>[x_1]&"] {
+            let formula = rpn_to_ast(expression).unwrap();
+            let rows = rows(&formula).unwrap();
+            for ((_, output), vars) in rows.clone().zip(assignments(rows.variables())) {
+                assert_eq!(output, formula.evaluate(&vars), "{}", expression);
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_variables_stream() {
+        // 2^18 rows, counted and written without materializing the table.
+        let expression: String =
+            (0..18).map(|i| format!("[x_{}]", i)).collect::<String>() + &"^".repeat(17);
+        let formula = rpn_to_ast(&expression).unwrap();
+        assert_eq!(
+            rows(&formula).unwrap().size_hint(),
+            (1 << 18, Some(1 << 18))
+        );
+        assert_eq!(
+            rows(&formula)
+                .unwrap()
+                .filter(|&(_, output)| output)
+                .count(),
+            1 << 17
+        );
+
+        struct CountingWriter(usize);
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0 += buf.iter().filter(|&&b| b == b'\n').count();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut out = CountingWriter(0);
+        write_truth_table(&formula, TruthTableFormat::Csv, &mut out).unwrap();
+        assert_eq!(out.0, (1 << 18) + 1);
+    }
+}